@@ -0,0 +1,59 @@
+// throughput of generating a large pattern and writing it out, run with: cargo bench
+// gorilla is a binary, so the built executable is timed as a whole
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+const PATTERN: &str = "{a-z}{a-z}{a-z}{a-z}{0-9}";
+// 26^4 * 10 words of 5 characters and a newline
+const WORDS: u64 = 26 * 26 * 26 * 26 * 10;
+const BYTES: u64 = WORDS * 6;
+const RUNS: usize = 3;
+
+fn run(args: &[&str]) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_gorilla"))
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("could not run gorilla");
+    let elapsed = start.elapsed();
+
+    assert!(status.success(), "gorilla {args:?} failed");
+    elapsed
+}
+
+// the fastest of a few runs
+fn bench(name: &str, args: &[&str]) {
+    let elapsed = (0..RUNS).map(|_| run(args)).min().unwrap();
+    let seconds = elapsed.as_secs_f64();
+
+    println!(
+        "{name:<12} {WORDS} words in {elapsed:>10.2?} ({:>11.0} words/s, {:>7.1} MB/s)",
+        WORDS as f64 / seconds,
+        BYTES as f64 / seconds / 1e6,
+    );
+}
+
+fn main() {
+    let dir = env::temp_dir().join("gorilla_output_bench");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("words.txt");
+    let gzip_path = dir.join("words.txt.gz");
+    let (path, gzip_path) = (path.to_str().unwrap(), gzip_path.to_str().unwrap());
+
+    println!("pattern {PATTERN}");
+    bench("stdout", &["-p", PATTERN]);
+    bench("file", &["-p", PATTERN, "-o", path]);
+    assert_eq!(fs::metadata(Path::new(path)).unwrap().len(), BYTES);
+    bench("gzip file", &["-p", PATTERN, "-o", gzip_path]);
+    bench("4 threads", &["-p", PATTERN, "-o", path, "--threads", "4"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+
+use crate::compression::Compression;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -7,13 +9,114 @@ use clap::{Parser, Subcommand};
     about = "a wordlist generator",
     long_about = "if you want to contribute to this project, check out the github repo: https://github.com/d4rckh/gorilla"
 )]
+#[clap(group(ArgGroup::new("split").args(["split_words", "split_bytes"]).multiple(true)))]
 pub struct ProgramArgs {
-    #[clap(short = 'i', long = "from-file", help = "Specify the input file")]
-    pub file_input: Option<String>,
+    #[clap(
+        short = 'i',
+        long = "from-file",
+        allow_hyphen_values = true,
+        help = "Specify an input file or directory, use - for stdin. Can be used multiple times"
+    )]
+    pub file_input: Vec<String>,
+
+    #[clap(
+        long = "invalid-lines",
+        value_enum,
+        default_value_t = InvalidLines::Escape,
+        help = "What to do with input lines that are not valid utf-8"
+    )]
+    pub invalid_lines: InvalidLines,
+
+    #[clap(
+        long = "hex-output",
+        help = "Write every word that isn't printable ascii using the $HEX[] notation"
+    )]
+    pub hex_output: bool,
+
+    #[clap(long = "min-length", help = "Drop generated words shorter than this")]
+    pub min_length: Option<usize>,
+
+    #[clap(long = "max-length", help = "Drop generated words longer than this")]
+    pub max_length: Option<usize>,
+
+    #[clap(
+        long = "require",
+        help = "Drop generated words missing one of these character classes (l, u, d, s)"
+    )]
+    pub require_classes: Option<String>,
+
+    #[clap(
+        long = "min-classes",
+        default_value_t = 0,
+        help = "Drop generated words containing less than this many character classes"
+    )]
+    pub min_classes: usize,
+
+    #[clap(
+        long = "allow-regex",
+        help = "Keep only generated words matching one of these regexes, can be used multiple times"
+    )]
+    pub allow_regex: Vec<String>,
+
+    #[clap(
+        long = "deny-regex",
+        help = "Drop generated words matching this regex, can be used multiple times"
+    )]
+    pub deny_regex: Vec<String>,
+
+    #[clap(
+        short = 'T',
+        long = "threads",
+        default_value_t = 1,
+        help = "Amount of threads used to mutate the words, 0 uses every available core"
+    )]
+    pub threads: usize,
+
+    #[clap(
+        long = "unordered",
+        help = "Write the words as soon as a thread is done with them, the output order can change"
+    )]
+    pub unordered: bool,
+
+    #[clap(
+        short = 'u',
+        long = "unique",
+        help = "Drop words that were already generated"
+    )]
+    pub unique: bool,
+
+    #[clap(
+        long = "unique-mode",
+        value_enum,
+        default_value_t = UniqueMode::Exact,
+        help = "How generated words are remembered by --unique"
+    )]
+    pub unique_mode: UniqueMode,
+
+    #[clap(
+        long = "unique-memory",
+        default_value_t = 256,
+        help = "Memory (in MB) used by the bloom filter of --unique-mode bloom"
+    )]
+    pub unique_memory: usize,
+
+    #[clap(
+        long = "input-delimiter",
+        value_parser = parse_separator,
+        help = "Delimiter between the words of the input files, escapes like \\0 and \\t are supported (default: new line)"
+    )]
+    pub input_delimiter: Option<String>,
 
     #[clap(short = 'l', long = "one-line", help = "Print the output on one line")]
     pub one_line: bool,
 
+    #[clap(
+        long = "output-separator",
+        value_parser = parse_separator,
+        help = "Separator written after every word, escapes like \\0 and \\t are supported (default: new line)"
+    )]
+    pub output_separator: Option<String>,
+
     #[clap(
         short = 't',
         long = "timer",
@@ -31,10 +134,16 @@ pub struct ProgramArgs {
     #[clap(
         short = 'q',
         long = "from-formatting",
-        help = "Generate words from custom formatting applied"
+        help = "Generate words from custom formatting applied, either a yaml file or a built-in set (profile, basic_usernames, corporate_usernames, corporate_emails)"
     )]
     pub from_formatting: Option<String>,
 
+    #[clap(
+        long = "top-formats",
+        help = "Only use the formats with the best rank (rank: in the formatting sets), unranked formats come after them in file order"
+    )]
+    pub top_formats: Option<usize>,
+
     #[clap(
         short = 'c',
         long = "with-csv",
@@ -42,6 +151,51 @@ pub struct ProgramArgs {
     )]
     pub csv: Option<String>,
 
+    #[clap(
+        long = "csv-map",
+        requires = "csv",
+        value_parser = parse_key_value,
+        help = "Use a CSV column for a formatting field (column=field), columns are numbered from 1 with --csv-no-headers"
+    )]
+    pub csv_map: Vec<(String, String)>,
+
+    #[clap(
+        long = "csv-ignore-extra",
+        requires = "csv",
+        help = "Ignore the CSV columns that don't match any formatting field"
+    )]
+    pub csv_ignore_extra: bool,
+
+    #[clap(
+        long = "csv-delimiter",
+        requires = "csv",
+        default_value = ",",
+        value_parser = parse_csv_delimiter,
+        help = "Character separating the CSV columns (\\t for tabs)"
+    )]
+    pub csv_delimiter: u8,
+
+    #[clap(
+        long = "csv-no-headers",
+        requires = "csv",
+        help = "The CSV has no header row, its columns are the formatting fields in order"
+    )]
+    pub csv_no_headers: bool,
+
+    #[clap(
+        long = "profile",
+        conflicts_with = "csv",
+        help = "Use a JSON/YAML file with one or more profiles as input for formatting fields"
+    )]
+    pub profile: Option<String>,
+
+    #[clap(
+        long = "field",
+        value_parser = parse_key_value,
+        help = "Answer a formatting field (name=value), added to every CSV row and profile"
+    )]
+    pub fields: Vec<(String, String)>,
+
     #[clap(
         short = 'w',
         long = "from-website",
@@ -49,6 +203,105 @@ pub struct ProgramArgs {
     )]
     pub website_input: Option<String>,
 
+    #[clap(
+        long = "from-combinator",
+        num_args = 2,
+        value_names = ["LEFT", "RIGHT"],
+        help = "Join every word of the left wordlist with every word of the right wordlist"
+    )]
+    pub combinator_input: Option<Vec<String>>,
+
+    #[clap(
+        long = "separator",
+        allow_hyphen_values = true,
+        help = "Specify a separator placed between the combined words, can be used multiple times"
+    )]
+    pub combinator_separators: Vec<String>,
+
+    #[clap(
+        long = "left-mutation",
+        help = "Specify a way to mutate the words of the left wordlist before combining them"
+    )]
+    pub left_mutation_string: Vec<String>,
+
+    #[clap(
+        long = "right-mutation",
+        help = "Specify a way to mutate the words of the right wordlist before combining them"
+    )]
+    pub right_mutation_string: Vec<String>,
+
+    #[clap(
+        long = "from-prince",
+        help = "Generate words by chaining the words of a wordlist (PRINCE)"
+    )]
+    pub prince_input: Option<String>,
+
+    #[clap(
+        long = "prince-elements",
+        default_value_t = 3,
+        help = "Maximum amount of words chained together"
+    )]
+    pub prince_elements: usize,
+
+    #[clap(
+        long = "prince-min-length",
+        default_value_t = 1,
+        help = "Minimum length of a chain"
+    )]
+    pub prince_min_length: usize,
+
+    #[clap(
+        long = "prince-max-length",
+        default_value_t = 16,
+        help = "Maximum length of a chain"
+    )]
+    pub prince_max_length: usize,
+
+    #[clap(
+        short = 'k',
+        long = "from-keywalk",
+        help = "Generate keyboard walks on a layout (qwerty, azerty, qwertz)"
+    )]
+    pub keywalk_input: Option<String>,
+
+    #[clap(
+        long = "walk-min-length",
+        default_value_t = 4,
+        help = "Minimum length of a keyboard walk"
+    )]
+    pub walk_min_length: usize,
+
+    #[clap(
+        long = "walk-max-length",
+        default_value_t = 8,
+        help = "Maximum length of a keyboard walk"
+    )]
+    pub walk_max_length: usize,
+
+    #[clap(
+        long = "walk-turns",
+        default_value_t = 2,
+        help = "Maximum amount of direction changes in a keyboard walk"
+    )]
+    pub walk_turns: usize,
+
+    #[clap(long = "walk-shift", help = "Use the shift layer in keyboard walks")]
+    pub walk_shift: bool,
+
+    #[clap(
+        long = "walk-shift-changes",
+        default_value_t = 1,
+        help = "Maximum amount of times shift is pressed/released in a keyboard walk"
+    )]
+    pub walk_shift_changes: usize,
+
+    #[clap(
+        long = "walk-segments",
+        default_value_t = 1,
+        help = "Maximum amount of parallel segments in a keyboard walk, 2 allows walks like 1qaz2wsx"
+    )]
+    pub walk_segments: usize,
+
     #[clap(
         short = 'o',
         long = "output-file",
@@ -56,6 +309,71 @@ pub struct ProgramArgs {
     )]
     pub file_save: Option<String>,
 
+    #[clap(
+        long = "append",
+        requires = "file_save",
+        conflicts_with_all = ["overwrite", "no_clobber"],
+        help = "Add the words to the end of the output file instead of replacing it"
+    )]
+    pub append: bool,
+
+    #[clap(
+        long = "overwrite",
+        requires = "file_save",
+        conflicts_with = "no_clobber",
+        help = "Replace the output file once every word is written (default)"
+    )]
+    pub overwrite: bool,
+
+    #[clap(
+        long = "no-clobber",
+        requires = "file_save",
+        help = "Exit with an error if the output file already exists"
+    )]
+    pub no_clobber: bool,
+
+    #[clap(
+        long = "format",
+        value_enum,
+        default_value_t = OutputFormat::Plain,
+        help = "Format of the output"
+    )]
+    pub format: OutputFormat,
+
+    #[clap(
+        long = "hash",
+        help = "Write every word followed by its hashes (md5, sha1, sha256, sha512, ntlm), comma separated"
+    )]
+    pub hash: Option<String>,
+
+    #[clap(
+        long = "crack",
+        requires = "hash",
+        help = "Only write the words whose hash is found in this file (one hash per line)"
+    )]
+    pub crack: Option<String>,
+
+    #[clap(
+        long = "split-words",
+        help = "Start a new numbered output file after this many words"
+    )]
+    pub split_words: Option<u64>,
+
+    #[clap(
+        long = "split-bytes",
+        value_parser = parse_size,
+        help = "Start a new numbered output file before it grows over this size (e.g. 500kb, 20mb, 1gb)"
+    )]
+    pub split_bytes: Option<u64>,
+
+    #[clap(
+        long = "split-compress",
+        value_parser = parse_compression,
+        requires = "split",
+        help = "Compress every numbered output file (gzip, zstd, bzip2, xz)"
+    )]
+    pub split_compress: Option<Compression>,
+
     #[clap(
         short = 'm',
         long = "mutation",
@@ -74,8 +392,121 @@ pub struct ProgramArgs {
     pub command: Option<Commands>,
 }
 
+// name=value
+fn parse_key_value(field: &str) -> Result<(String, String), String> {
+    match field.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.to_owned()))
+        }
+        _ => Err(format!("expected name=value, got {field}")),
+    }
+}
+
+fn parse_csv_delimiter(delimiter: &str) -> Result<u8, String> {
+    match parse_separator(delimiter)?.as_bytes() {
+        [delimiter] => Ok(*delimiter),
+        _ => Err(String::from(
+            "the delimiter has to be a single ascii character",
+        )),
+    }
+}
+
+// \0, \t, \n, \r and \\ are replaced with the characters they stand for
+pub fn parse_separator(separator: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut chars = separator.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => result.push('\0'),
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => return Err(format!("unknown escape \\{other}")),
+            None => return Err(String::from("trailing \\")),
+        }
+    }
+
+    if result.is_empty() {
+        return Err(String::from("the separator can't be empty"));
+    }
+
+    Ok(result)
+}
+
+// 20mb => 20971520
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.to_lowercase();
+    let digits_end = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(digits_end);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {size}"))?;
+
+    let multiplier: u64 = match unit.trim() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1048576,
+        "g" | "gb" => 1073741824,
+        _ => return Err(format!("invalid size unit: {unit}")),
+    };
+
+    number
+        .checked_mul(multiplier)
+        .ok_or(format!("size is too large: {size}"))
+}
+
+fn parse_compression(compression: &str) -> Result<Compression, String> {
+    match compression {
+        "gzip" | "gz" => Ok(Compression::Gzip),
+        "zstd" | "zst" => Ok(Compression::Zstd),
+        "bzip2" | "bz2" => Ok(Compression::Bzip2),
+        "xz" => Ok(Compression::Xz),
+        _ => Err(format!("unknown compression: {compression}")),
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    // one word per line
+    Plain,
+    // one json object per line with the original word and the mutations that generated the word
+    Jsonl,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum InvalidLines {
+    // keep the bytes and write the word using the $HEX[] notation
+    Escape,
+    // silently skip the line
+    Skip,
+    // print a warning and skip the line
+    Report,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum UniqueMode {
+    // keep every word in memory
+    Exact,
+    // bounded memory, a few unique words may be dropped
+    Bloom,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     // does testing things
     // list_mutations {    },
+    #[clap(
+        about = "Generate passwords from a personal profile (names, birthdate, pet, company...) using the built-in formatting sets"
+    )]
+    Profile,
 }
@@ -0,0 +1,26 @@
+// formatting sets that are compiled into the binary, so they work without the sets directory
+pub const PROFILE: &str = include_str!("../sets/formatting/profile.yml");
+
+// selectable by name with --from-formatting
+pub const FORMATTING_SETS: [(&str, &str); 4] = [
+    ("profile", PROFILE),
+    (
+        "basic_usernames",
+        include_str!("../sets/formatting/basic_usernames.yml"),
+    ),
+    (
+        "corporate_usernames",
+        include_str!("../sets/formatting/corporate_usernames.yml"),
+    ),
+    (
+        "corporate_emails",
+        include_str!("../sets/formatting/corporate_emails.yml"),
+    ),
+];
+
+pub fn find_formatting_sets(name: &str) -> Option<&'static str> {
+    FORMATTING_SETS
+        .iter()
+        .find(|(set_name, _)| *set_name == name)
+        .map(|(_, yaml)| *yaml)
+}
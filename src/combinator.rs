@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+
+use crate::mutation::MutationSet;
+
+/*
+joins every word of the left wordlist with every word of the right one
+(hashcat's -a 1). the left wordlist is streamed, only the right one is kept
+in memory since it's walked once per left word.
+
+the order is: left word, its mutations, separators, right words
+*/
+pub struct Combinator {
+    left_mutations: MutationSet,
+    separators: Vec<String>,
+    right_words: Vec<String>,
+}
+
+// duplicates would only generate the same combinations again
+fn unique(words: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    words
+        .into_iter()
+        .filter(|w| seen.insert(w.to_owned()))
+        .collect()
+}
+
+impl Combinator {
+    // the right side is mutated only once
+    pub fn new(
+        right_words: impl Iterator<Item = String>,
+        left_mutations: MutationSet,
+        right_mutations: &MutationSet,
+        separators: Vec<String>,
+    ) -> Combinator {
+        let separators = if separators.is_empty() {
+            vec![String::new()]
+        } else {
+            unique(separators)
+        };
+
+        Combinator {
+            left_mutations,
+            separators,
+            right_words: unique(right_words.flat_map(|w| right_mutations.mutate(&w))),
+        }
+    }
+
+    pub fn combine(&self, left_word: &str, mut emit: impl FnMut(String)) {
+        for left_word in unique(self.left_mutations.mutate(left_word)) {
+            for separator in &self.separators {
+                for right_word in &self.right_words {
+                    emit(format!("{left_word}{separator}{right_word}"));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Read, Write},
+};
+
+use bzip2::{read::MultiBzDecoder, write::BzEncoder};
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+use xz2::{read::XzDecoder, write::XzEncoder};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+            Compression::Bzip2 => write!(f, "bzip2"),
+            Compression::Xz => write!(f, "xz"),
+        }
+    }
+}
+
+// BZh, the block size (1-9) and the magic of the first block (or of the end
+// of an empty stream), wordlists can start with BZh too
+fn is_bzip2(bytes: &[u8]) -> bool {
+    const BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+    const END_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+
+    match bytes {
+        [b'B', b'Z', b'h', b'1'..=b'9', magic @ ..] => {
+            magic.starts_with(&BLOCK_MAGIC) || magic.starts_with(&END_MAGIC)
+        }
+        _ => false,
+    }
+}
+
+impl Compression {
+    pub fn from_extension(path: &str) -> Compression {
+        let path = path.to_lowercase();
+
+        if path.ends_with(".gz") {
+            Compression::Gzip
+        } else if path.ends_with(".zst") {
+            Compression::Zstd
+        } else if path.ends_with(".bz2") {
+            Compression::Bzip2
+        } else if path.ends_with(".xz") {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    pub fn from_magic(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if is_bzip2(bytes) {
+            Compression::Bzip2
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
+// peeks at the first bytes of the stream to find out how it's compressed,
+// falling back to the extension of the path
+pub fn decompress_reader(
+    reader: Box<dyn Read>,
+    path: &str,
+) -> io::Result<(Box<dyn BufRead>, Compression)> {
+    let mut reader = BufReader::new(reader);
+
+    let mut compression = Compression::from_magic(reader.fill_buf()?);
+    if compression == Compression::None {
+        compression = Compression::from_extension(path)
+    }
+
+    let reader: Box<dyn BufRead> = match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+        Compression::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+    };
+
+    Ok((reader, compression))
+}
+
+// kept as the concrete encoder, so the trailer is written by finish and its errors aren't lost
+pub enum CompressedWriter {
+    None(Box<dyn Write>),
+    Gzip(GzEncoder<Box<dyn Write>>),
+    Zstd(zstd::Encoder<'static, Box<dyn Write>>),
+    Bzip2(BzEncoder<Box<dyn Write>>),
+    Xz(XzEncoder<Box<dyn Write>>),
+}
+
+impl CompressedWriter {
+    fn inner(&mut self) -> &mut dyn Write {
+        match self {
+            CompressedWriter::None(writer) => writer,
+            CompressedWriter::Gzip(encoder) => encoder,
+            CompressedWriter::Zstd(encoder) => encoder,
+            CompressedWriter::Bzip2(encoder) => encoder,
+            CompressedWriter::Xz(encoder) => encoder,
+        }
+    }
+
+    // writes the trailer of the compressed stream and flushes the underlying writer
+    pub fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            CompressedWriter::None(writer) => writer,
+            CompressedWriter::Gzip(encoder) => encoder.finish()?,
+            CompressedWriter::Zstd(encoder) => encoder.finish()?,
+            CompressedWriter::Bzip2(encoder) => encoder.finish()?,
+            CompressedWriter::Xz(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner().write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner().flush()
+    }
+}
+
+pub fn compress_writer(
+    writer: Box<dyn Write>,
+    compression: Compression,
+) -> io::Result<CompressedWriter> {
+    Ok(match compression {
+        Compression::None => CompressedWriter::None(writer),
+        Compression::Gzip => {
+            CompressedWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+        }
+        Compression::Zstd => CompressedWriter::Zstd(zstd::Encoder::new(writer, 0)?),
+        Compression::Bzip2 => {
+            CompressedWriter::Bzip2(BzEncoder::new(writer, bzip2::Compression::default()))
+        }
+        Compression::Xz => CompressedWriter::Xz(XzEncoder::new(writer, 6)),
+    })
+}
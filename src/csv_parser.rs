@@ -1,35 +1,115 @@
 use colored::Colorize;
 
-use crate::formatting::FormatFieldAnswer;
+use crate::formatting::{push_answer, FormatField, FormatFieldAnswer};
 
-pub fn fmt_answers_from_csv(csv_path: &str) -> Vec<Vec<FormatFieldAnswer>> {
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub has_headers: bool,
+    // column -> field, columns are numbered from 1 when there are no headers
+    pub column_map: Vec<(String, String)>,
+    // columns that don't belong to any field are dropped instead of stopping gorilla
+    pub ignore_extra_columns: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            delimiter: b',',
+            has_headers: true,
+            column_map: Vec::new(),
+            ignore_extra_columns: false,
+        }
+    }
+}
+
+// the field every column is used for, None for ignored columns
+fn map_columns(
+    columns: &[String],
+    fields: &[FormatField],
+    options: &CsvOptions,
+) -> Result<Vec<Option<String>>, String> {
+    for (column, _) in &options.column_map {
+        if !columns.contains(column) {
+            eprintln!("gorilla: (warning) column {column} from --csv-map not found");
+        }
+    }
+
+    let mut result = Vec::new();
+
+    for (column_no, column) in columns.iter().enumerate() {
+        let mapped = options.column_map.iter().find(|(c, _)| c == column);
+
+        let field = match mapped {
+            Some((_, field)) => Some(field.as_str()),
+            None if options.has_headers => Some(column.as_str()),
+            // columns of headerless csvs are the fields in order
+            None => fields.get(column_no).map(|f| f.name.as_str()),
+        };
+
+        match field {
+            Some(field) if fields.iter().any(|f| f.name == field) => {
+                result.push(Some(field.to_owned()))
+            }
+            _ if options.ignore_extra_columns => result.push(None),
+            _ => {
+                let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+                return Err(format!(
+                    "column {column} doesn't match any field. Valid names: {names:?} (use --csv-map or --csv-ignore-extra)"
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn fmt_answers_from_csv(
+    csv_path: &str,
+    fields: &[FormatField],
+    options: &CsvOptions,
+) -> Result<Vec<Vec<FormatFieldAnswer>>, String> {
     let mut answers: Vec<Vec<FormatFieldAnswer>> = Vec::new();
 
-    let mut rdr = csv::Reader::from_path(csv_path).unwrap();
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .from_path(csv_path)
+        .map_err(|e| e.to_string())?;
 
     eprintln!("gorilla: parsing {} csv file", csv_path.purple());
 
-    let mut headers: Vec<String> = Vec::new();
+    let columns: Vec<String> = if options.has_headers {
+        let headers = rdr.headers().map_err(|e| e.to_string())?;
+        headers.iter().map(|h| h.trim().to_owned()).collect()
+    } else {
+        // the first record tells how many columns there are
+        let count = rdr.headers().map_err(|e| e.to_string())?.len();
+        (1..=count).map(|n| n.to_string()).collect()
+    };
 
-    for header in rdr.headers().unwrap() {
-        headers.push(header.to_owned())
-    }
+    let column_fields = map_columns(&columns, fields, options)?;
 
     for result in rdr.records() {
-        let row = result.unwrap();
+        // a broken row doesn't stop the others from being used
+        let row = match result {
+            Ok(row) => row,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                eprintln!("gorilla: (warning) skipping csv row at line {line}: {e}");
+                continue;
+            }
+        };
 
         let mut row_answers: Vec<FormatFieldAnswer> = Vec::new();
-        let mut column_no = 0;
-
-        for answer in row.iter() {
-            let name = headers[column_no].to_owned();
-            let answer = answer.to_owned();
-            row_answers.push(FormatFieldAnswer { name, answer });
-            column_no += 1
+        for (field, answer) in column_fields.iter().zip(row.iter()) {
+            // repeated columns (pet,pet,pet) add values to the same field
+            if let Some(field) = field {
+                push_answer(&mut row_answers, field, answer);
+            }
         }
 
         answers.push(row_answers);
     }
 
-    answers
+    Ok(answers)
 }
@@ -0,0 +1,100 @@
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+// placeholders derived from a date field, used as {dob.ddmm}
+pub const DATE_COMPONENTS: [&str; 14] = [
+    "d", "dd", "m", "mm", "yy", "yyyy", "ddmm", "mmdd", "ddmmyy", "mmddyy", "ddmmyyyy", "yyyymmdd",
+    "month", "mon",
+];
+
+fn days_in_month(month: u32, year: u32) -> u32 {
+    let leap_year =
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+
+    match month {
+        2 if leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Date {
+    pub day: u32,
+    pub month: u32,
+    pub year: u32,
+}
+
+impl Date {
+    // DDMMYYYY, DD/MM/YYYY (or with - and .) and YYYY-MM-DD
+    pub fn parse(date: &str) -> Option<Date> {
+        let date = date.trim();
+        // checked before slicing, multibyte characters would split in the middle
+        if !date
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"/-.".contains(&b))
+        {
+            return None;
+        }
+
+        let numbers: Vec<&str> = date.split(['/', '-', '.']).collect();
+
+        let (day, month, year) = match numbers[..] {
+            [digits] if digits.len() == 8 => (&digits[..2], &digits[2..4], &digits[4..]),
+            [year, month, day] if year.len() == 4 => (day, month, year),
+            [day, month, year] if year.len() == 4 => (day, month, year),
+            _ => return None,
+        };
+
+        let date = Date {
+            day: day.parse().ok()?,
+            month: month.parse().ok()?,
+            year: year.parse().ok()?,
+        };
+
+        if (1..=12).contains(&date.month)
+            && (1..=days_in_month(date.month, date.year)).contains(&date.day)
+        {
+            Some(date)
+        } else {
+            None
+        }
+    }
+
+    pub fn component(&self, component: &str) -> Option<String> {
+        let Date { day, month, year } = self;
+        let yy = year % 100;
+        let month_name = MONTHS[*month as usize - 1];
+
+        Some(match component {
+            "d" => day.to_string(),
+            "dd" => format!("{day:02}"),
+            "m" => month.to_string(),
+            "mm" => format!("{month:02}"),
+            "yy" => format!("{yy:02}"),
+            "yyyy" => format!("{year:04}"),
+            "ddmm" => format!("{day:02}{month:02}"),
+            "mmdd" => format!("{month:02}{day:02}"),
+            "ddmmyy" => format!("{day:02}{month:02}{yy:02}"),
+            "mmddyy" => format!("{month:02}{day:02}{yy:02}"),
+            "ddmmyyyy" => format!("{day:02}{month:02}{year:04}"),
+            "yyyymmdd" => format!("{year:04}{month:02}{day:02}"),
+            "month" => month_name.to_owned(),
+            "mon" => month_name[..3].to_owned(),
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::HashSet,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+// amount of bits set for every word in the bloom filter
+const BLOOM_HASHES: u64 = 7;
+
+pub enum Deduplicator {
+    // remembers every word, never drops a unique word
+    Exact(HashSet<String>),
+    // uses a fixed amount of memory, but can drop unique words (false positives)
+    Bloom(BloomFilter),
+}
+
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+}
+
+impl Deduplicator {
+    pub fn exact() -> Deduplicator {
+        Deduplicator::Exact(HashSet::new())
+    }
+
+    pub fn bloom(memory_mb: usize) -> Deduplicator {
+        Deduplicator::Bloom(BloomFilter::new(memory_mb.max(1) * 1048576))
+    }
+
+    // returns true if the word wasn't seen before
+    pub fn insert(&mut self, word: &str) -> bool {
+        match self {
+            Deduplicator::Exact(seen) => {
+                if seen.contains(word) {
+                    false
+                } else {
+                    seen.insert(word.to_owned())
+                }
+            }
+            Deduplicator::Bloom(filter) => filter.insert(word),
+        }
+    }
+}
+
+impl BloomFilter {
+    pub fn new(size_bytes: usize) -> BloomFilter {
+        let words = (size_bytes / 8).max(1);
+
+        BloomFilter {
+            bits: vec![0; words],
+            bit_count: words as u64 * 64,
+        }
+    }
+
+    fn hash(word: &str, seed: u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        word.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // returns true if at least one of the bits wasn't set before
+    pub fn insert(&mut self, word: &str) -> bool {
+        // double hashing: bit i is h1 + i * h2
+        let h1 = BloomFilter::hash(word, 0);
+        let h2 = BloomFilter::hash(word, 1) | 1;
+        let mut new_word = false;
+
+        for i in 0..BLOOM_HASHES {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.bit_count;
+            let (index, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+
+            if self.bits[index] & mask == 0 {
+                new_word = true;
+                self.bits[index] |= mask;
+            }
+        }
+
+        new_word
+    }
+}
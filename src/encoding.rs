@@ -0,0 +1,117 @@
+use std::borrow::Cow;
+
+/*
+words are Strings everywhere in gorilla, so bytes that are not valid utf-8 are
+mapped to private use characters (U+F780 - U+F7FF) when a line is read. they go
+through the mutations like any other character and are turned back into the
+original bytes when the word is written. characters from that range found in
+the input are escaped byte by byte as well, so they can't be mistaken for
+escaped bytes.
+*/
+
+const ESCAPE_BASE: u32 = 0xF700;
+const HEX_PREFIX: &str = "$HEX[";
+
+pub fn escape_bytes(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+
+    for chunk in bytes.utf8_chunks() {
+        for ch in chunk.valid().chars() {
+            if escaped_byte(ch).is_some() {
+                ch.encode_utf8(&mut [0; 4])
+                    .bytes()
+                    .for_each(|b| push_escaped(&mut result, b));
+            } else {
+                result.push(ch);
+            }
+        }
+        for byte in chunk.invalid() {
+            push_escaped(&mut result, *byte);
+        }
+    }
+
+    result
+}
+
+// only bytes that are not ascii are escaped, they are never valid utf-8 on their own
+fn push_escaped(result: &mut String, byte: u8) {
+    result.push(char::from_u32(ESCAPE_BASE + byte as u32).unwrap());
+}
+
+fn escaped_byte(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    if (ESCAPE_BASE + 0x80..=ESCAPE_BASE + 0xff).contains(&code) {
+        Some((code - ESCAPE_BASE) as u8)
+    } else {
+        None
+    }
+}
+
+pub fn unescape_bytes(word: &str) -> Vec<u8> {
+    let mut result = Vec::with_capacity(word.len());
+
+    for ch in word.chars() {
+        match escaped_byte(ch) {
+            Some(byte) => result.push(byte),
+            None => result.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    result
+}
+
+// hashcat's $HEX[68656c6c6f] notation
+pub fn decode_hex_notation(line: &[u8]) -> Option<Vec<u8>> {
+    let hex = line
+        .strip_prefix(HEX_PREFIX.as_bytes())?
+        .strip_suffix(b"]")?;
+
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+// lowercase hex, two characters per byte
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        result.push_str(&format!("{byte:02x}"));
+    }
+    result
+}
+
+pub fn encode_hex_notation(bytes: &[u8]) -> String {
+    format!("{HEX_PREFIX}{}]", encode_hex(bytes))
+}
+
+// Err contains the escaped word when the line is not valid utf-8. $HEX[] lines
+// are always decoded, invalid utf-8 inside them is escaped
+pub fn decode_word(line: &[u8]) -> Result<String, String> {
+    if let Some(bytes) = decode_hex_notation(line) {
+        return Ok(escape_bytes(&bytes));
+    }
+
+    match std::str::from_utf8(line) {
+        Ok(word) if word.chars().any(|ch| escaped_byte(ch).is_some()) => Ok(escape_bytes(line)),
+        Ok(word) => Ok(word.to_owned()),
+        Err(_) => Err(escape_bytes(line)),
+    }
+}
+
+// words that contain bytes that are not valid utf-8 are always written using
+// the $HEX[] notation, force_hex also encodes every word that isn't printable ascii
+pub fn encode_word(word: &str, force_hex: bool) -> Cow<'_, str> {
+    let needs_hex = word.starts_with(HEX_PREFIX)
+        || word.chars().any(|ch| escaped_byte(ch).is_some())
+        || (force_hex && word.chars().any(|ch| !(' '..='~').contains(&ch)));
+
+    if needs_hex {
+        Cow::Owned(encode_hex_notation(&unescape_bytes(word)))
+    } else {
+        Cow::Borrowed(word)
+    }
+}
@@ -0,0 +1,101 @@
+use regex::Regex;
+
+use crate::char_sets;
+
+// same letters as the character sets used in patterns ({l}, {u}, {d}, {s})
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Special,
+}
+
+#[derive(Default)]
+pub struct OutputFilter {
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    // every class has to be present in the word
+    pub required_classes: Vec<CharClass>,
+    // at least this many different classes have to be present in the word
+    pub min_classes: usize,
+    // the word has to match at least one of them
+    pub allow: Vec<Regex>,
+    // the word is dropped if it matches any of them
+    pub deny: Vec<Regex>,
+}
+
+impl CharClass {
+    pub fn from_char(ch: char) -> Option<CharClass> {
+        match ch {
+            'l' => Some(CharClass::Lowercase),
+            'u' => Some(CharClass::Uppercase),
+            'd' => Some(CharClass::Digit),
+            's' => Some(CharClass::Special),
+            _ => None,
+        }
+    }
+
+    pub fn of(ch: char) -> Option<CharClass> {
+        if char_sets::L_CH.contains(ch) {
+            Some(CharClass::Lowercase)
+        } else if char_sets::U_CH.contains(ch) {
+            Some(CharClass::Uppercase)
+        } else if char_sets::D_CH.contains(ch) {
+            Some(CharClass::Digit)
+        } else if char_sets::S_CH.contains(ch) {
+            Some(CharClass::Special)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn parse_char_classes(classes: &str) -> Result<Vec<CharClass>, String> {
+    classes
+        .chars()
+        .map(|ch| CharClass::from_char(ch).ok_or(format!("unknown character class {ch}")))
+        .collect()
+}
+
+impl OutputFilter {
+    pub fn is_empty(&self) -> bool {
+        self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.required_classes.is_empty()
+            && self.min_classes == 0
+            && self.allow.is_empty()
+            && self.deny.is_empty()
+    }
+
+    pub fn matches(&self, word: &str) -> bool {
+        let length = word.chars().count();
+
+        if self.min_length.is_some_and(|min| length < min)
+            || self.max_length.is_some_and(|max| length > max)
+        {
+            return false;
+        }
+
+        if !self.required_classes.is_empty() || self.min_classes > 0 {
+            let mut present: Vec<CharClass> = Vec::new();
+            for class in word.chars().filter_map(CharClass::of) {
+                if !present.contains(&class) {
+                    present.push(class)
+                }
+            }
+
+            if present.len() < self.min_classes
+                || !self.required_classes.iter().all(|c| present.contains(c))
+            {
+                return false;
+            }
+        }
+
+        if !self.allow.is_empty() && !self.allow.iter().any(|r| r.is_match(word)) {
+            return false;
+        }
+
+        !self.deny.iter().any(|r| r.is_match(word))
+    }
+}
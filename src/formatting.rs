@@ -1,4 +1,4 @@
-use crate::mutation::{MutationResult, MutationSet};
+use crate::{dates::Date, mutation::MutationSet, templating::Placeholder};
 
 pub struct FormatPart {
     pub text: String,
@@ -7,14 +7,28 @@ pub struct FormatPart {
 
 pub struct FormatSet {
     pub parts: Vec<FormatPart>,
+    // how common the format is, 1 being the most common. used by --top-formats
+    pub rank: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldType {
+    Text,
+    // parsed from the answer, adds {field.dd}, {field.yyyy}... (see dates::DATE_COMPONENTS)
+    Date,
 }
 
 pub struct FormatField {
     pub name: String,
     pub question: Option<String>,
+    pub field_type: FieldType,
+    // formatting sets are skipped when a required field has no answer, the parts
+    // using an optional field are left out instead
+    pub optional: bool,
 }
 
 pub struct FormattingSets {
+    #[allow(dead_code)]
     pub name: String,
     pub fields: Vec<FormatField>,
     pub sets: Vec<FormatSet>,
@@ -23,17 +37,62 @@ pub struct FormattingSets {
 #[derive(Debug)]
 pub struct FormatFieldAnswer {
     pub name: String,
-    pub answer: String,
+    // a field can have many values (nicknames, pets, children), every formatting
+    // set is generated for all of them. no values means an empty answer
+    pub values: Vec<String>,
+    pub field_type: FieldType,
+    pub optional: bool,
 }
 
 impl FormatFieldAnswer {
-    pub fn placeholder(&self) -> String {
-        let mut result = String::new();
-        result.push('{');
-        result.push_str(&self.name);
-        result.push('}');
+    // values are separated by commas: "rex, fluffy"
+    pub fn new(name: &str, answer: &str) -> FormatFieldAnswer {
+        let mut result = FormatFieldAnswer {
+            name: name.to_owned(),
+            values: Vec::new(),
+            field_type: FieldType::Text,
+            optional: false,
+        };
+        result.push_values(answer);
         result
     }
+
+    pub fn push_values(&mut self, answer: &str) {
+        for value in answer.split(',') {
+            self.push_value(value)
+        }
+    }
+
+    // a single value, even if it contains commas
+    pub fn push_value(&mut self, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() && !self.values.iter().any(|v| v == value) {
+            self.values.push(value.to_owned())
+        }
+    }
+
+    fn value(&self, index: usize) -> &str {
+        self.values.get(index).map_or("", String::as_str)
+    }
+
+    // what a placeholder of this field is replaced with for one of its values
+    fn fill(&self, placeholder: &Placeholder, value: &str) -> String {
+        // None if the field wasn't answered
+        let date = match self.field_type {
+            FieldType::Date => Date::parse(value),
+            FieldType::Text => None,
+        };
+
+        placeholder.fill(value, date.as_ref())
+    }
+}
+
+// adds the values to the answer of the field, the same field can be given more than once
+pub fn push_answer(answers: &mut Vec<FormatFieldAnswer>, name: &str, answer: &str) {
+    match answers.iter_mut().find(|a| a.name == name) {
+        Some(field_answer) => field_answer.push_values(answer),
+        None => answers.push(FormatFieldAnswer::new(name, answer)),
+    }
 }
 
 impl FormattingSets {
@@ -51,40 +110,275 @@ impl FormattingSets {
         }
     }
 
-    // TODO: foloseste un iterator in loc de functia asta jegoasa
-    pub fn generate_words(&self, answers: Vec<FormatFieldAnswer>) -> Vec<String> {
-        let mut result: Vec<String> = Vec::new();
+    // placeholders that don't belong to any field and fields that are never used
+    pub fn check_placeholders(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut used: Vec<&str> = Vec::new();
 
-        for fmt_set in &self.sets {
-            let mut final_string = String::new();
+        for part in self.sets.iter().flat_map(|set| &set.parts) {
+            for placeholder in placeholders(&part.text) {
+                let parsed = Placeholder::parse(placeholder);
 
-            for part in &fmt_set.parts {
-                let mut p_text = part.text.to_owned();
+                let problem = match self.fields.iter().find(|f| f.name == parsed.field) {
+                    Some(field) => match parsed.check(field.field_type == FieldType::Date) {
+                        Ok(()) => None,
+                        Err(e) => Some(format!("invalid placeholder {{{placeholder}}}: {e}")),
+                    },
+                    None => Some(format!("unknown placeholder {{{placeholder}}}")),
+                };
 
-                for answer in &answers {
-                    p_text = p_text.replace(&answer.placeholder(), &answer.answer)
+                match problem {
+                    Some(problem) if !problems.contains(&problem) => problems.push(problem),
+                    Some(_) => (),
+                    None if !used.contains(&parsed.field) => used.push(parsed.field),
+                    None => (),
                 }
+            }
+        }
 
-                let mut mutation_result = MutationResult {
-                    original_word: p_text.to_owned(),
-                    mutated_words: Vec::new(),
-                };
+        for field in &self.fields {
+            if !used.contains(&field.name.as_str()) {
+                problems.push(format!(
+                    "field {} is not used by any formatting set",
+                    field.name
+                ));
+            }
+        }
+
+        problems
+    }
+
+    // keeps the formatting sets with the best rank, the unranked ones come after
+    // them in file order
+    pub fn keep_top_formats(&mut self, count: usize) {
+        self.sets.sort_by_key(|set| set.rank.unwrap_or(u32::MAX));
+        self.sets.truncate(count);
+    }
+
+    // every word of every formatting set, generated lazily
+    pub fn generate_words(&self, mut answers: Vec<FormatFieldAnswer>) -> FormattedWords<'_> {
+        // fields missing from a csv or a profile aren't answered
+        for field in &self.fields {
+            if !answers.iter().any(|a| a.name == field.name) {
+                answers.push(FormatFieldAnswer::new(&field.name, ""))
+            }
+        }
+
+        for answer in &mut answers {
+            let field = self.fields.iter().find(|f| f.name == answer.name);
+            answer.field_type = field.map_or(FieldType::Text, |f| f.field_type);
+            answer.optional = field.is_some_and(|f| f.optional);
 
-                part.mutations.perform(&mut mutation_result, &p_text);
-                if !mutation_result.mutated_words.is_empty() {
-                    final_string.push_str(&mutation_result.mutated_words[0])
+            if answer.field_type == FieldType::Date {
+                let name = &answer.name;
+                answer.values.retain(|value| {
+                    let valid = Date::parse(value).is_some();
+                    if !valid {
+                        eprintln!(
+                            "gorilla: (warning) {value} is not a valid date for {name}, expected DDMMYYYY, DD/MM/YYYY or YYYY-MM-DD"
+                        );
+                    }
+                    valid
+                })
+            }
+        }
+
+        FormattedWords {
+            sets: self.sets.iter(),
+            answers,
+            current_set: None,
+            used_answers: Vec::new(),
+            value_indices: Vec::new(),
+            values_done: true,
+            parts: Vec::new(),
+            part_indices: Vec::new(),
+            parts_done: true,
+        }
+    }
+}
+
+// names between braces: "{f_name}.{dob.yy}" -> ["f_name", "dob.yy"]
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut rest = text;
+
+    while let Some((start, end)) = next_placeholder(rest) {
+        result.push(&rest[start + 1..end]);
+        rest = &rest[end + 1..];
+    }
+
+    result
+}
+
+// position of the braces of the first placeholder
+fn next_placeholder(text: &str) -> Option<(usize, usize)> {
+    let start = text.find('{')?;
+    let end = start + text[start..].find('}')?;
+    Some((start, end))
+}
+
+// replaces every placeholder in a single pass, so values containing {...} are
+// written as they are. placeholders of unknown fields are kept
+fn fill_placeholders(text: &str, values: &[(&FormatFieldAnswer, &str)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some((start, end)) = next_placeholder(rest) {
+        result.push_str(&rest[..start]);
+
+        let placeholder = Placeholder::parse(&rest[start + 1..end]);
+        match values.iter().find(|(a, _)| a.name == placeholder.field) {
+            Some((answer, value)) => result.push_str(&answer.fill(&placeholder, value)),
+            None => result.push_str(&rest[start..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+// advances the indices like an odometer, the last one changing the fastest.
+// returns false after the last combination
+fn next_combination(indices: &mut [usize], lengths: &[usize]) -> bool {
+    for (index, &length) in indices.iter_mut().zip(lengths).rev() {
+        *index += 1;
+        if *index < length {
+            return true;
+        }
+        *index = 0;
+    }
+    false
+}
+
+/*
+every formatting set is generated once for each combination of the values of the
+fields it uses. the words of a combination are the cartesian product of the words
+generated by each part: the parts are mutated once, then combined.
+*/
+pub struct FormattedWords<'a> {
+    sets: std::slice::Iter<'a, FormatSet>,
+    answers: Vec<FormatFieldAnswer>,
+    current_set: Option<&'a FormatSet>,
+    // answers whose placeholders appear in the current set
+    used_answers: Vec<usize>,
+    // the value of each used answer for the current combination
+    value_indices: Vec<usize>,
+    values_done: bool,
+    // mutated words of every part for the current combination
+    parts: Vec<Vec<String>>,
+    // the word of each part used for the next result
+    part_indices: Vec<usize>,
+    parts_done: bool,
+}
+
+impl Iterator for FormattedWords<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.parts_done {
+            if self.values_done {
+                let fmt_set = self.sets.next()?;
+
+                self.current_set = Some(fmt_set);
+                self.used_answers = (0..self.answers.len())
+                    .filter(|&i| fmt_set.uses(&self.answers[i]))
+                    .collect();
+                self.value_indices = vec![0; self.used_answers.len()];
+
+                let missing = |&i: &usize| {
+                    let answer = &self.answers[i];
+                    answer.values.is_empty() && !answer.optional
+                };
+                if self.used_answers.iter().any(missing) {
+                    continue;
                 }
             }
 
-            result.push(final_string)
+            let fmt_set = self.current_set.unwrap();
+            let values: Vec<(&FormatFieldAnswer, &str)> = self
+                .used_answers
+                .iter()
+                .zip(&self.value_indices)
+                .map(|(&a, &v)| (&self.answers[a], self.answers[a].value(v)))
+                .collect();
+
+            // the parts using an optional field without an answer are left out
+            let left_out: Vec<bool> = fmt_set
+                .parts
+                .iter()
+                .map(|part| {
+                    values
+                        .iter()
+                        .any(|(a, _)| a.values.is_empty() && part.uses(a))
+                })
+                .collect();
+
+            let parts: Vec<Vec<String>> = if left_out.iter().all(|&l| l) {
+                vec![vec![]]
+            } else {
+                fmt_set
+                    .parts
+                    .iter()
+                    .zip(&left_out)
+                    .map(|(part, &left_out)| match left_out {
+                        true => vec![String::new()],
+                        false => part.generate_words(&values),
+                    })
+                    .collect()
+            };
+
+            let lengths: Vec<usize> = self
+                .used_answers
+                .iter()
+                .map(|&a| self.answers[a].values.len().max(1))
+                .collect();
+            self.values_done = !next_combination(&mut self.value_indices, &lengths);
+
+            // a part without words (e.g. filtered by a condition) leaves nothing to combine
+            self.parts_done = parts.iter().any(Vec::is_empty);
+            self.part_indices = vec![0; parts.len()];
+            self.parts = parts;
         }
 
-        result
+        let word: String = self
+            .parts
+            .iter()
+            .zip(&self.part_indices)
+            .map(|(words, &i)| words[i].as_str())
+            .collect();
+
+        let lengths: Vec<usize> = self.parts.iter().map(Vec::len).collect();
+        self.parts_done = !next_combination(&mut self.part_indices, &lengths);
+
+        Some(word)
+    }
+}
+
+impl FormatPart {
+    fn uses(&self, answer: &FormatFieldAnswer) -> bool {
+        // {dob.yyyy} and {f_name|first:2} count as well
+        placeholders(&self.text)
+            .into_iter()
+            .any(|p| Placeholder::parse(p).field == answer.name)
+    }
+
+    fn generate_words(&self, values: &[(&FormatFieldAnswer, &str)]) -> Vec<String> {
+        self.mutations
+            .mutate(&fill_placeholders(&self.text, values))
     }
 }
 
 impl FormatSet {
     pub fn new() -> FormatSet {
-        FormatSet { parts: Vec::new() }
+        FormatSet {
+            parts: Vec::new(),
+            rank: None,
+        }
+    }
+
+    fn uses(&self, answer: &FormatFieldAnswer) -> bool {
+        self.parts.iter().any(|part| part.uses(answer))
     }
 }
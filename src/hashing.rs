@@ -0,0 +1,110 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    io::{self, BufRead},
+};
+
+use md4::Md4;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{
+    encoding::{encode_hex, unescape_bytes},
+    input::open_input,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Ntlm,
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashAlgorithm::Md5 => write!(f, "md5"),
+            HashAlgorithm::Sha1 => write!(f, "sha1"),
+            HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Sha512 => write!(f, "sha512"),
+            HashAlgorithm::Ntlm => write!(f, "ntlm"),
+        }
+    }
+}
+
+impl HashAlgorithm {
+    pub fn from_string(algorithm: &str) -> Option<HashAlgorithm> {
+        match algorithm.to_lowercase().replace('-', "").as_str() {
+            "md5" => Some(HashAlgorithm::Md5),
+            "sha1" => Some(HashAlgorithm::Sha1),
+            "sha256" => Some(HashAlgorithm::Sha256),
+            "sha512" => Some(HashAlgorithm::Sha512),
+            "ntlm" => Some(HashAlgorithm::Ntlm),
+            _ => None,
+        }
+    }
+
+    // hex encoded hash of the word, bytes escaped by the input reader are hashed as they were read
+    pub fn hash(&self, word: &str) -> String {
+        let bytes = unescape_bytes(word);
+
+        match self {
+            HashAlgorithm::Md5 => encode_hex(&Md5::digest(&bytes)),
+            HashAlgorithm::Sha1 => encode_hex(&Sha1::digest(&bytes)),
+            HashAlgorithm::Sha256 => encode_hex(&Sha256::digest(&bytes)),
+            HashAlgorithm::Sha512 => encode_hex(&Sha512::digest(&bytes)),
+            HashAlgorithm::Ntlm => {
+                let utf16: Vec<u8> = String::from_utf8_lossy(&bytes)
+                    .encode_utf16()
+                    .flat_map(|c| c.to_le_bytes())
+                    .collect();
+                encode_hex(&Md4::digest(&utf16))
+            }
+        }
+    }
+}
+
+pub fn parse_hash_algorithms(algorithms: &str) -> Result<Vec<HashAlgorithm>, String> {
+    algorithms
+        .split(',')
+        .map(|a| HashAlgorithm::from_string(a.trim()).ok_or(format!("unknown hash algorithm {a}")))
+        .collect()
+}
+
+// hashes that are looked up by --crack, one per line
+pub struct HashTargets {
+    hashes: HashSet<String>,
+}
+
+impl HashTargets {
+    pub fn from_file(path: &str) -> io::Result<HashTargets> {
+        let mut hashes = HashSet::new();
+
+        for line in open_input(path)?.lines() {
+            let line = line?;
+            let hash = line.trim();
+            if !hash.is_empty() {
+                hashes.insert(hash.to_lowercase());
+            }
+        }
+
+        Ok(HashTargets { hashes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn matches(&self, word: &str, algorithms: &[HashAlgorithm]) -> bool {
+        algorithms
+            .iter()
+            .any(|a| self.hashes.contains(&a.hash(word)))
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, Read},
+    path::Path,
+};
+
+use crate::{
+    arguments::InvalidLines,
+    compression::{decompress_reader, Compression},
+    encoding::{decode_word, encode_hex_notation},
+};
+
+// amount of bytes checked when deciding if a file found in a directory is a text file
+const TEXT_SNIFF_LEN: usize = 8192;
+
+pub const STDIN_PATH: &str = "-";
+
+// expands directories (recursively) into the text (or compressed) files found inside them,
+// "-" and regular files are kept as they are
+pub fn expand_input_paths(paths: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+
+    for path in paths {
+        if path != STDIN_PATH && Path::new(path).is_dir() {
+            walk_dir(Path::new(path), &mut result);
+        } else {
+            result.push(path.to_owned())
+        }
+    }
+
+    result
+}
+
+// symlinked directories are skipped, they can link back to a parent
+fn walk_dir(dir: &Path, result: &mut Vec<String>) {
+    let mut entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| Some((e.path(), e.file_type().ok()?)))
+            .collect(),
+        Err(e) => {
+            eprintln!(
+                "gorilla: (warning) could not read directory {}: {e}",
+                dir.display()
+            );
+            return;
+        }
+    };
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (entry, file_type) in entries {
+        if file_type.is_dir() {
+            walk_dir(&entry, result)
+        } else if (file_type.is_file() || (file_type.is_symlink() && entry.is_file()))
+            && is_text_file(&entry)
+        {
+            result.push(entry.to_string_lossy().into_owned())
+        }
+    }
+}
+
+fn is_text_file(path: &Path) -> bool {
+    let mut buffer = Vec::new();
+
+    match File::open(path) {
+        Ok(file) => match file.take(TEXT_SNIFF_LEN as u64).read_to_end(&mut buffer) {
+            Ok(_) => Compression::from_magic(&buffer) != Compression::None || !buffer.contains(&0),
+            Err(_) => false,
+        },
+        Err(_) => false,
+    }
+}
+
+// compressed inputs are decompressed on the fly
+pub fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let reader: Box<dyn Read> = if path == STDIN_PATH {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path)?)
+    };
+
+    let (reader, compression) = decompress_reader(reader, path)?;
+    if compression != Compression::None {
+        eprintln!("gorilla: decompressing {compression} input");
+    }
+
+    Ok(reader)
+}
+
+pub const DEFAULT_DELIMITER: &str = "\n";
+
+pub struct WordLines {
+    reader: Box<dyn BufRead>,
+    delimiter: Vec<u8>,
+}
+
+// like BufRead::lines but the lines are returned as bytes so that
+// lines that are not valid utf-8 can still be read, and they can be
+// delimited by any string (a trailing \r is removed only when it's \n)
+pub fn word_lines(reader: Box<dyn BufRead>, delimiter: &str) -> WordLines {
+    WordLines {
+        reader,
+        delimiter: delimiter.as_bytes().to_vec(),
+    }
+}
+
+impl Iterator for WordLines {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        let last_byte = *self.delimiter.last()?;
+
+        // read until the last byte of the delimiter until the whole delimiter is found
+        loop {
+            match self.reader.read_until(last_byte, &mut line) {
+                Ok(0) if line.is_empty() => return None,
+                Ok(0) => return Some(Ok(line)),
+                Ok(_) => {
+                    if line.ends_with(&self.delimiter) {
+                        line.truncate(line.len() - self.delimiter.len());
+                        if self.delimiter == b"\n" && line.ends_with(b"\r") {
+                            line.pop();
+                        }
+                        return Some(Ok(line));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// decoded words of an input, lines that are not valid utf-8 are handled as --invalid-lines says
+pub struct Words {
+    lines: WordLines,
+    path: String,
+    invalid_lines: InvalidLines,
+    line_no: usize,
+    // lines that were left out
+    pub skipped: usize,
+}
+
+pub fn read_words(path: &str, delimiter: &str, invalid_lines: InvalidLines) -> io::Result<Words> {
+    Ok(Words {
+        lines: word_lines(open_input(path)?, delimiter),
+        path: path.to_owned(),
+        invalid_lines,
+        line_no: 0,
+        skipped: 0,
+    })
+}
+
+impl Iterator for Words {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line_no += 1;
+
+            match decode_word(&line) {
+                Ok(word) => return Some(Ok(word)),
+                Err(escaped) => match self.invalid_lines {
+                    InvalidLines::Escape => return Some(Ok(escaped)),
+                    InvalidLines::Skip => self.skipped += 1,
+                    InvalidLines::Report => {
+                        eprintln!(
+                            "gorilla: (warning) {}:{} is not valid utf-8: {}",
+                            self.path,
+                            self.line_no,
+                            encode_hex_notation(&line)
+                        );
+                        self.skipped += 1
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt::{self, Display};
+
+/*
+keys are laid out on a grid where every row is shifted a bit to the right
+compared to the row above it, so the key at (row, col) touches:
+  (row, col - 1) and (row, col + 1) on the same row
+  (row - 1, col) and (row - 1, col + 1) above
+  (row + 1, col - 1) and (row + 1, col) below
+
+a walk can also be made of parallel segments: once the first segment is done,
+the walk jumps to a key next to its start and repeats the same moves from there.
+1qaz2wsx is 1qaz followed by 2wsx, qwerasdf is qwer followed by asdf
+*/
+
+// (unshifted, shifted) characters of each row, top to bottom
+const QWERTY_ROWS: [(&str, &str); 4] = [
+    ("1234567890-=", "!@#$%^&*()_+"),
+    ("qwertyuiop[]", "QWERTYUIOP{}"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+const AZERTY_ROWS: [(&str, &str); 4] = [
+    ("&é\"'(-è_çà)=", "1234567890°+"),
+    ("azertyuiop^$", "AZERTYUIOP¨£"),
+    ("qsdfghjklmù*", "QSDFGHJKLM%µ"),
+    ("wxcvbn,;:!", "WXCVBN?./§"),
+];
+
+const QWERTZ_ROWS: [(&str, &str); 4] = [
+    ("1234567890ß´", "!\"§$%&/()=?`"),
+    ("qwertzuiopü+", "QWERTZUIOPÜ*"),
+    ("asdfghjklöä#", "ASDFGHJKLÖÄ'"),
+    ("yxcvbnm,.-", "YXCVBNM;:_"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+const DIRECTIONS: [Direction; 6] = [
+    Direction::Left,
+    Direction::Right,
+    Direction::UpLeft,
+    Direction::UpRight,
+    Direction::DownLeft,
+    Direction::DownRight,
+];
+
+pub struct Key {
+    pub normal: char,
+    pub shifted: char,
+    // neighbour key indexes, in the same order as DIRECTIONS
+    neighbours: [Option<usize>; 6],
+}
+
+pub struct Keyboard {
+    pub keys: Vec<Key>,
+}
+
+pub struct KeyWalkOptions {
+    pub min_length: usize,
+    pub max_length: usize,
+    // how many times the walk is allowed to change its direction
+    pub max_turns: usize,
+    // allow using the shift layer of the keyboard
+    pub shift: bool,
+    // how many times shift can be pressed/released in the middle of a walk
+    pub max_shift_changes: usize,
+    // 1 walks from key to key, more allows parallel segments (1qaz2wsx)
+    pub max_segments: usize,
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Layout::Qwerty => write!(f, "qwerty"),
+            Layout::Azerty => write!(f, "azerty"),
+            Layout::Qwertz => write!(f, "qwertz"),
+        }
+    }
+}
+
+impl Layout {
+    pub fn from_string(layout: &str) -> Option<Layout> {
+        match layout.to_lowercase().as_str() {
+            "qwerty" => Some(Layout::Qwerty),
+            "azerty" => Some(Layout::Azerty),
+            "qwertz" => Some(Layout::Qwertz),
+            _ => None,
+        }
+    }
+
+    fn rows(&self) -> [(&'static str, &'static str); 4] {
+        match self {
+            Layout::Qwerty => QWERTY_ROWS,
+            Layout::Azerty => AZERTY_ROWS,
+            Layout::Qwertz => QWERTZ_ROWS,
+        }
+    }
+}
+
+impl Direction {
+    fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 0),
+        }
+    }
+
+    fn reverse(&self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+}
+
+impl Keyboard {
+    pub fn new(layout: Layout) -> Keyboard {
+        let rows: Vec<(Vec<char>, Vec<char>)> = layout
+            .rows()
+            .iter()
+            .map(|(normal, shifted)| (normal.chars().collect(), shifted.chars().collect()))
+            .collect();
+
+        let key_index = |row: isize, col: isize| -> Option<usize> {
+            if row < 0 || col < 0 || row as usize >= rows.len() {
+                return None;
+            }
+            if col as usize >= rows[row as usize].0.len() {
+                return None;
+            }
+            let before: usize = rows[..row as usize].iter().map(|r| r.0.len()).sum();
+            Some(before + col as usize)
+        };
+
+        let mut keys: Vec<Key> = Vec::new();
+
+        for (row_no, (normal, shifted)) in rows.iter().enumerate() {
+            for (col_no, normal_ch) in normal.iter().enumerate() {
+                let mut neighbours = [None; 6];
+                for (i, direction) in DIRECTIONS.iter().enumerate() {
+                    let (row_off, col_off) = direction.offset();
+                    neighbours[i] = key_index(row_no as isize + row_off, col_no as isize + col_off);
+                }
+
+                keys.push(Key {
+                    normal: *normal_ch,
+                    shifted: shifted[col_no],
+                    neighbours,
+                })
+            }
+        }
+
+        Keyboard { keys }
+    }
+
+    pub fn neighbour(&self, key: usize, direction: Direction) -> Option<usize> {
+        let i = DIRECTIONS.iter().position(|d| *d == direction).unwrap();
+        self.keys[key].neighbours[i]
+    }
+
+    pub fn walks(&self, options: KeyWalkOptions) -> KeyWalkIter<'_> {
+        let mut stack = Vec::new();
+
+        if options.max_length > 0 {
+            // pushed in reverse so the walks come out in keyboard order
+            for key in (0..self.keys.len()).rev() {
+                let layers: &[bool] = if options.shift {
+                    &[true, false]
+                } else {
+                    &[false]
+                };
+                for shifted in layers {
+                    stack.push(WalkState {
+                        word: String::from(self.keys[key].char(*shifted)),
+                        length: 1,
+                        key,
+                        shifted: *shifted,
+                        direction: None,
+                        turns: 0,
+                        shift_changes: 0,
+                        moves: Vec::new(),
+                        segment_start: key,
+                        replayed: 0,
+                        segments: 1,
+                    })
+                }
+            }
+        }
+
+        KeyWalkIter {
+            keyboard: self,
+            options,
+            stack,
+        }
+    }
+}
+
+impl Key {
+    fn char(&self, shifted: bool) -> char {
+        if shifted {
+            self.shifted
+        } else {
+            self.normal
+        }
+    }
+}
+
+struct WalkState {
+    word: String,
+    length: usize,
+    key: usize,
+    shifted: bool,
+    direction: Option<Direction>,
+    turns: usize,
+    shift_changes: usize,
+    // moves of the first segment, repeated by the parallel ones
+    moves: Vec<Direction>,
+    segment_start: usize,
+    // moves of the first segment repeated by the current one
+    replayed: usize,
+    segments: usize,
+}
+
+pub struct KeyWalkIter<'a> {
+    keyboard: &'a Keyboard,
+    options: KeyWalkOptions,
+    stack: Vec<WalkState>,
+}
+
+impl KeyWalkIter<'_> {
+    // the next keys of a walk, without the shift changes
+    fn steps(&self, state: &WalkState) -> Vec<WalkState> {
+        let mut steps = Vec::new();
+        let step = |key: usize| WalkState {
+            word: String::new(),
+            length: state.length + 1,
+            key,
+            shifted: state.shifted,
+            direction: state.direction,
+            turns: state.turns,
+            shift_changes: state.shift_changes,
+            moves: state.moves.clone(),
+            segment_start: state.segment_start,
+            replayed: state.replayed,
+            segments: state.segments,
+        };
+
+        if state.segments == 1 {
+            for direction in DIRECTIONS {
+                let Some(next_key) = self.keyboard.neighbour(state.key, direction) else {
+                    continue;
+                };
+
+                let turns = match state.direction {
+                    Some(d) if d != direction => state.turns + 1,
+                    _ => state.turns,
+                };
+                if turns > self.options.max_turns {
+                    continue;
+                }
+
+                let mut next = step(next_key);
+                next.direction = Some(direction);
+                next.turns = turns;
+                next.moves.push(direction);
+                steps.push(next);
+            }
+        } else if let Some(&direction) = state.moves.get(state.replayed) {
+            if let Some(next_key) = self.keyboard.neighbour(state.key, direction) {
+                let mut next = step(next_key);
+                next.replayed += 1;
+                steps.push(next);
+            }
+        }
+
+        let segment_done = state.segments == 1 || state.replayed == state.moves.len();
+        if state.segments < self.options.max_segments && !state.moves.is_empty() && segment_done {
+            for direction in DIRECTIONS {
+                // along the segment the keys would be repeated
+                if state
+                    .moves
+                    .iter()
+                    .any(|m| *m == direction || m.reverse() == direction)
+                {
+                    continue;
+                }
+
+                if let Some(next_key) = self.keyboard.neighbour(state.segment_start, direction) {
+                    let mut next = step(next_key);
+                    next.segment_start = next_key;
+                    next.replayed = 0;
+                    next.segments += 1;
+                    steps.push(next);
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+impl Iterator for KeyWalkIter<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(state) = self.stack.pop() {
+            if state.length < self.options.max_length {
+                let mut children = Vec::new();
+
+                for step in self.steps(&state) {
+                    let toggled = (self.options.shift
+                        && state.shift_changes < self.options.max_shift_changes)
+                        .then(|| WalkState {
+                            word: String::new(),
+                            shifted: !step.shifted,
+                            shift_changes: step.shift_changes + 1,
+                            moves: step.moves.clone(),
+                            ..step
+                        });
+
+                    children.push(step);
+                    children.extend(toggled);
+                }
+
+                // pushed in reverse so the first direction is walked first
+                for mut child in children.into_iter().rev() {
+                    child.word = state.word.clone();
+                    child
+                        .word
+                        .push(self.keyboard.keys[child.key].char(child.shifted));
+                    self.stack.push(child)
+                }
+            }
+
+            if state.length >= self.options.min_length {
+                return Some(state.word);
+            }
+        }
+
+        None
+    }
+}
@@ -1,116 +1,372 @@
 mod arguments;
+mod built_in_sets;
 mod char_sets;
+mod combinator;
+mod compression;
 mod csv_parser;
+mod dates;
+mod dedup;
+mod encoding;
+mod filters;
 mod formatting;
+mod hashing;
+mod input;
+mod keyboard_walk;
 mod mutation;
+mod output;
 mod patterns;
+mod pipeline;
+mod prince;
+mod profile_parser;
+mod templating;
+mod transliteration;
 mod website_scraper;
 mod yaml_parser;
 
 mod tests;
 
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{self, BufRead, BufReader, Write},
+    fs,
+    io::{self, Write},
+    sync::Arc,
+    thread,
     time::SystemTime,
 };
 
 use clap::Parser;
 use colored::Colorize;
 use mutation::MutationResult;
+use regex::Regex;
 
 use crate::{
-    arguments::ProgramArgs,
-    csv_parser::fmt_answers_from_csv,
-    formatting::FormatFieldAnswer,
+    arguments::{Commands, InvalidLines, ProgramArgs, UniqueMode},
+    built_in_sets::{find_formatting_sets, FORMATTING_SETS},
+    combinator::Combinator,
+    compression::Compression,
+    csv_parser::{fmt_answers_from_csv, CsvOptions},
+    dedup::Deduplicator,
+    filters::{parse_char_classes, OutputFilter},
+    formatting::{FormatFieldAnswer, FormattingSets},
+    hashing::{parse_hash_algorithms, HashTargets},
+    input::{expand_input_paths, read_words, DEFAULT_DELIMITER, STDIN_PATH},
+    keyboard_walk::{KeyWalkOptions, Keyboard, Layout},
     mutation::{parse_mutation_string, MutationSet},
+    output::{OutputWriter, SplitOptions, WriteMode},
     patterns::{token_iterator, tokenize_format_string},
+    pipeline::{mutate_word, WorkerPool, BATCH_SIZE},
+    prince::{prince_iterator, PrinceOptions},
+    profile_parser::{fmt_answers_from_profile, push_field_answers},
     website_scraper::{download_page, extract_words},
     yaml_parser::{get_mutation_sets, parse_formatting_yaml},
 };
 
 struct Gorilla {
     program_args: ProgramArgs,
-    mutation_sets: Vec<MutationSet>,
-    file_save: Option<File>,
-    mutation_counter: u32,
-    word_counter: u32,
+    mutation_sets: Arc<Vec<MutationSet>>,
+    pool: Option<WorkerPool>,
+    batch: Vec<String>,
+    output: OutputWriter,
+    filter: OutputFilter,
+    deduplicator: Option<Deduplicator>,
+    hash_targets: Option<HashTargets>,
+    mutation_counter: u64,
+    word_counter: u64,
+    filtered_counter: u64,
+    duplicate_counter: u64,
+    cracked_counter: u64,
     start_time: SystemTime,
-    output_separator: String,
 }
 
 impl Gorilla {
     fn mutate_word(&mut self, word: String) {
-        let mut mutation_result = MutationResult {
-            original_word: word.clone(),
-            mutated_words: vec![],
-        };
-
         self.word_counter += 1;
 
-        for mutation_set in &self.mutation_sets {
-            mutation_set.perform(&mut mutation_result, &word);
+        if let Some(pool) = &mut self.pool {
+            self.batch.push(word);
 
-            if let Some(save_file) = &mut self.file_save {
-                mutation_result.save_to_file(save_file)
+            if self.batch.len() >= BATCH_SIZE {
+                let batch = std::mem::take(&mut self.batch);
+                for (set_index, mutation_result) in pool.submit(batch).into_iter().flatten() {
+                    self.save_result(set_index, mutation_result)
+                }
             }
 
-            for s in &mutation_result.mutated_words {
-                self.mutation_counter += 1;
+            return;
+        }
 
-                if self.file_save.is_some() {
-                    continue;
-                }
+        for (set_index, mutation_result) in mutate_word(&self.mutation_sets, &word) {
+            self.save_result(set_index, mutation_result);
+        }
+    }
 
-                if self.program_args.timer {
-                    eprint!(
-                        "(in {:?}) ",
-                        SystemTime::now()
-                            .duration_since(self.start_time)
-                            .expect("time may have gone backwards")
-                    );
-                }
+    // output filters first, so the filtered words aren't remembered by the deduplicator
+    fn filter_words(&mut self, words: &mut Vec<String>) {
+        if !self.filter.is_empty() {
+            let before = words.len();
+            words.retain(|w| self.filter.matches(w));
+            self.filtered_counter += (before - words.len()) as u64;
+        }
+
+        if let Some(deduplicator) = &mut self.deduplicator {
+            let before = words.len();
+            words.retain(|w| deduplicator.insert(w));
+            self.duplicate_counter += (before - words.len()) as u64;
+        }
+
+        if let Some(hash_targets) = &self.hash_targets {
+            let algorithms = &self.output.hash_algorithms;
+            words.retain(|w| hash_targets.matches(w, algorithms));
+            self.cracked_counter += words.len() as u64;
+        }
+    }
+
+    fn save_result(&mut self, set_index: usize, mut mutation_result: MutationResult) {
+        self.filter_words(&mut mutation_result.mutated_words);
+        self.mutation_counter += mutation_result.mutated_words.len() as u64;
+
+        if !self.program_args.timer || self.program_args.file_save.is_some() {
+            mutation_result.save_to_file(&mut self.output, set_index);
+            return;
+        }
 
-                print!("{s}{}", self.output_separator)
+        // flushed after every word so the timings line up with the words
+        for s in &mutation_result.mutated_words {
+            eprint!(
+                "(in {:?}) ",
+                SystemTime::now()
+                    .duration_since(self.start_time)
+                    .expect("time may have gone backwards")
+            );
+
+            self.output
+                .write_candidate(s, &mutation_result.original_word, set_index)
+                .expect("write failed");
+            self.output.flush().expect("write failed");
+        }
+    }
+
+    // sends the last batch to the workers and waits for them
+    fn finish(&mut self) {
+        if let Some(mut pool) = self.pool.take() {
+            let batch = std::mem::take(&mut self.batch);
+            let mut results = pool.submit(batch);
+            results.append(&mut pool.finish());
+
+            for (set_index, mutation_result) in results.into_iter().flatten() {
+                self.save_result(set_index, mutation_result)
             }
         }
     }
 }
 
+fn build_output_filter(program_args: &ProgramArgs) -> OutputFilter {
+    let build_regex = |r: &String| match Regex::new(r) {
+        Ok(regex) => regex,
+        Err(e) => {
+            eprintln!("gorilla: (error) invalid regex {r}: {e}");
+            panic!()
+        }
+    };
+
+    let required_classes = match &program_args.require_classes {
+        Some(classes) => match parse_char_classes(classes) {
+            Ok(classes) => classes,
+            Err(e) => {
+                eprintln!("gorilla: (error) {e}. Valid classes: l, u, d, s");
+                panic!()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    OutputFilter {
+        min_length: program_args.min_length,
+        max_length: program_args.max_length,
+        required_classes,
+        min_classes: program_args.min_classes,
+        allow: program_args.allow_regex.iter().map(build_regex).collect(),
+        deny: program_args.deny_regex.iter().map(build_regex).collect(),
+    }
+}
+
+fn side_mutation_set(mutation_strings: &Vec<String>) -> MutationSet {
+    if mutation_strings.is_empty() {
+        MutationSet::empty_set()
+    } else {
+        MutationSet {
+            mutations: parse_mutation_string(mutation_strings),
+        }
+    }
+}
+
+// the words of the wordlists used by the combinator and PRINCE, errors stop gorilla
+fn wordlist(path: &str, invalid_lines: InvalidLines) -> impl Iterator<Item = String> + '_ {
+    let words = read_words(path, DEFAULT_DELIMITER, invalid_lines).unwrap_or_else(|e| {
+        eprintln!("gorilla: (error) could not open {path}: {e}");
+        panic!();
+    });
+
+    words.map(move |word| {
+        word.unwrap_or_else(|e| {
+            eprintln!("gorilla: (error) could not read {path}: {e}");
+            panic!();
+        })
+    })
+}
+
+// asks for every field of the formatting sets
+fn ask_answers(fmt_sets: &FormattingSets) -> Vec<FormatFieldAnswer> {
+    let mut fmt_answers: Vec<FormatFieldAnswer> = Vec::new();
+    eprintln!("gorilla: separate multiple answers with commas");
+
+    for q in &fmt_sets.fields {
+        let mut buffer = String::new();
+
+        if let Some(question) = &q.question {
+            eprint!("(?) {}", question.blue())
+        } else {
+            eprint!("(?) Fill in {}", q.name.blue())
+        }
+        eprint!("{}: ", if q.optional { " (optional)" } else { "" });
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut buffer).unwrap();
+
+        fmt_answers.push(FormatFieldAnswer::new(&q.name, &buffer))
+    }
+
+    fmt_answers
+}
+
 fn main() {
+    let program_args = ProgramArgs::parse();
+
+    let separator = match &program_args.output_separator {
+        Some(separator) => separator.to_owned(),
+        None if program_args.one_line => String::from(' '),
+        None => String::from('\n'),
+    };
+
+    // the modes conflict with each other, overwrite is used when none is given
+    let write_mode = match (
+        program_args.append,
+        program_args.overwrite,
+        program_args.no_clobber,
+    ) {
+        (true, _, _) => WriteMode::Append,
+        (_, _, true) => WriteMode::NoClobber,
+        (false, true, false) | (false, false, false) => WriteMode::Overwrite,
+    };
+
+    let output = match &program_args.file_save {
+        Some(file_save) => {
+            eprintln!("gorilla: using file {} as output", file_save.purple());
+
+            let split = program_args.split_words.is_some() || program_args.split_bytes.is_some();
+
+            // chunks are compressed with --split-compress when it's given
+            let compression = match program_args.split_compress {
+                Some(compression) if split => compression,
+                _ => Compression::from_extension(file_save),
+            };
+            if compression != Compression::None {
+                eprintln!("gorilla: compressing output with {compression}");
+            }
+
+            if split {
+                let options = SplitOptions {
+                    max_words: program_args.split_words,
+                    max_bytes: program_args.split_bytes,
+                    compression: program_args.split_compress,
+                };
+
+                OutputWriter::split_files(
+                    file_save,
+                    &separator,
+                    program_args.hex_output,
+                    write_mode,
+                    options,
+                )
+            } else {
+                OutputWriter::file(file_save, &separator, program_args.hex_output, write_mode)
+            }
+            .unwrap_or_else(|e| {
+                eprintln!("gorilla: (error) could not open output file: {e}");
+                panic!();
+            })
+        }
+        None => OutputWriter::stdout(&separator, program_args.hex_output),
+    };
+
     let mut gorilla = Gorilla {
-        program_args: ProgramArgs::parse(),
-        mutation_sets: vec![],
-        file_save: None,
+        program_args,
+        mutation_sets: Arc::new(vec![]),
+        pool: None,
+        batch: Vec::new(),
+        output,
+        filter: OutputFilter::default(),
+        deduplicator: None,
+        hash_targets: None,
         mutation_counter: 0,
         word_counter: 0,
+        filtered_counter: 0,
+        duplicate_counter: 0,
+        cracked_counter: 0,
         start_time: SystemTime::now(),
-        output_separator: String::from('\n'),
     };
 
-    if gorilla.program_args.one_line {
-        gorilla.output_separator = String::from(' ')
+    gorilla.filter = build_output_filter(&gorilla.program_args);
+
+    if let Some(hash) = &gorilla.program_args.hash {
+        gorilla.output.hash_algorithms = match parse_hash_algorithms(hash) {
+            Ok(algorithms) => algorithms,
+            Err(e) => {
+                eprintln!(
+                    "gorilla: (error) {e}. Valid algorithms: md5, sha1, sha256, sha512, ntlm"
+                );
+                panic!()
+            }
+        }
+    }
+
+    if let Some(crack) = &gorilla.program_args.crack {
+        let hash_targets = HashTargets::from_file(crack).expect("could not open hash file");
+        if hash_targets.is_empty() {
+            eprintln!("gorilla: (warning) no hashes found in {crack}");
+        }
+        eprintln!(
+            "gorilla: looking for {} hashes from {}",
+            hash_targets.len(),
+            crack.purple()
+        );
+        gorilla.hash_targets = Some(hash_targets)
+    }
+
+    if gorilla.program_args.unique {
+        gorilla.deduplicator = Some(match gorilla.program_args.unique_mode {
+            UniqueMode::Exact => Deduplicator::exact(),
+            UniqueMode::Bloom => Deduplicator::bloom(gorilla.program_args.unique_memory),
+        })
     }
 
+    let mut mutation_sets: Vec<MutationSet> = Vec::new();
+
     if !gorilla.program_args.mutation_string.is_empty() {
-        gorilla.mutation_sets.push(MutationSet {
+        mutation_sets.push(MutationSet {
             mutations: parse_mutation_string(&gorilla.program_args.mutation_string),
         })
     }
 
     if let Some(mutations_file) = &gorilla.program_args.mutations_file {
         let yaml_input = &fs::read_to_string(mutations_file).unwrap();
-        gorilla
-            .mutation_sets
-            .append(&mut get_mutation_sets(yaml_input))
+        mutation_sets.append(&mut get_mutation_sets(yaml_input))
     }
 
-    if gorilla.mutation_sets.is_empty() {
+    if mutation_sets.is_empty() {
         eprintln!("gorilla: (warning) missing mutation sets");
-        gorilla.mutation_sets.push(MutationSet::empty_set())
+        mutation_sets.push(MutationSet::empty_set())
     } else {
         eprintln!("gorilla: mutation sets summary");
-        for mutation_set in &gorilla.mutation_sets {
+        for mutation_set in &mutation_sets {
             eprint!(" {}", "word".dimmed());
             for mutation in &mutation_set.mutations {
                 eprint!(" -> {}", mutation.to_string().blue());
@@ -119,71 +375,174 @@ fn main() {
         }
     }
 
-    if let Some(formatting_path) = &gorilla.program_args.from_formatting {
-        let yaml_input = &fs::read_to_string(formatting_path)
-            .expect("could not open file containing custom formats");
-        let fmt_sets = parse_formatting_yaml(yaml_input);
+    gorilla.output.format = gorilla.program_args.format;
+    gorilla.output.mutation_chains = mutation_sets.iter().map(|m| m.describe()).collect();
+    gorilla.mutation_sets = Arc::new(mutation_sets);
 
-        if let Some(csv_path) = &gorilla.program_args.csv {
-            let answer_sets = fmt_answers_from_csv(csv_path);
-            fmt_sets.check_answer_names(answer_sets.first().unwrap());
+    let threads = match gorilla.program_args.threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
 
-            for fmt_answers in answer_sets {
-                for gen_word in fmt_sets.generate_words(fmt_answers) {
-                    gorilla.mutate_word(gen_word);
-                }
-            }
-        } else {
-            let mut fmt_answers: Vec<FormatFieldAnswer> = Vec::new();
+    if threads > 1 {
+        eprintln!(
+            "gorilla: mutating words on {} threads",
+            threads.to_string().purple()
+        );
+        gorilla.pool = Some(WorkerPool::new(
+            threads,
+            Arc::clone(&gorilla.mutation_sets),
+            !gorilla.program_args.unordered,
+        ))
+    }
 
-            for q in &fmt_sets.fields {
-                let mut buffer = String::new();
+    let fmt_sets = match &gorilla.program_args.command {
+        Some(Commands::Profile) => Some(parse_formatting_yaml(built_in_sets::PROFILE)),
+        None => gorilla
+            .program_args
+            .from_formatting
+            .as_ref()
+            .map(|path| match fs::read_to_string(path) {
+                Ok(yaml_input) => parse_formatting_yaml(&yaml_input),
+                Err(e) => match find_formatting_sets(path) {
+                    Some(yaml_input) => parse_formatting_yaml(yaml_input),
+                    None => {
+                        let names: Vec<&str> = FORMATTING_SETS.iter().map(|(n, _)| *n).collect();
+                        eprintln!(
+                            "gorilla: (error) could not open {path}: {e}. Built-in formatting sets: {names:?}"
+                        );
+                        panic!()
+                    }
+                },
+            }),
+    };
 
-                if let Some(question) = &q.question {
-                    eprint!("(?) {}: ", question.blue())
-                } else {
-                    eprint!("(?) Fill in {}: ", q.name.blue())
-                }
-                io::stdout().flush().unwrap();
-                io::stdin().read_line(&mut buffer).unwrap();
+    if let Some(mut fmt_sets) = fmt_sets {
+        if let Some(top_formats) = gorilla.program_args.top_formats {
+            fmt_sets.keep_top_formats(top_formats);
+        }
 
-                fmt_answers.push(FormatFieldAnswer {
-                    name: q.name.to_owned(),
-                    answer: buffer.trim().to_owned(),
-                })
-            }
+        let program_args = &gorilla.program_args;
+
+        let mut answer_sets = if let Some(csv_path) = &program_args.csv {
+            let options = CsvOptions {
+                delimiter: program_args.csv_delimiter,
+                has_headers: !program_args.csv_no_headers,
+                column_map: program_args.csv_map.clone(),
+                ignore_extra_columns: program_args.csv_ignore_extra,
+            };
+
+            fmt_answers_from_csv(csv_path, &fmt_sets.fields, &options).unwrap_or_else(|e| {
+                eprintln!("gorilla: (error) could not parse csv {csv_path}: {e}");
+                panic!()
+            })
+        } else if let Some(profile_path) = &program_args.profile {
+            fmt_answers_from_profile(profile_path).unwrap_or_else(|e| {
+                eprintln!("gorilla: (error) could not parse profile {profile_path}: {e}");
+                panic!()
+            })
+        } else if !program_args.fields.is_empty() {
+            vec![Vec::new()]
+        } else {
+            let fmt_answers = ask_answers(&fmt_sets);
 
             // reset start time bcuz we dont want to time how much it took the user to
             // answer the questions
             gorilla.start_time = SystemTime::now();
 
+            vec![fmt_answers]
+        };
+
+        for fmt_answers in &mut answer_sets {
+            push_field_answers(fmt_answers, &gorilla.program_args.fields);
+            fmt_sets.check_answer_names(fmt_answers);
+        }
+
+        for fmt_answers in answer_sets {
             for gen_word in fmt_sets.generate_words(fmt_answers) {
                 gorilla.mutate_word(gen_word);
             }
         }
     }
 
-    if let Some(file_save) = &gorilla.program_args.file_save {
-        eprintln!("gorilla: using file {} as output", file_save.purple());
-        gorilla.file_save = Some(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(file_save)
-                .expect("Could not output file"),
-        )
+    for file_input in expand_input_paths(&gorilla.program_args.file_input) {
+        if file_input == STDIN_PATH {
+            eprintln!("gorilla: reading words from {}", "stdin".purple());
+        } else {
+            eprintln!("gorilla: reading words from {}", file_input.purple());
+        }
+
+        let delimiter = gorilla
+            .program_args
+            .input_delimiter
+            .as_deref()
+            .unwrap_or(DEFAULT_DELIMITER);
+
+        let mut words = match read_words(&file_input, delimiter, gorilla.program_args.invalid_lines)
+        {
+            Ok(words) => words,
+            Err(e) => {
+                eprintln!("gorilla: (error) could not open {file_input}: {e}");
+                continue;
+            }
+        };
+
+        for word in &mut words {
+            match word {
+                Ok(word) => gorilla.mutate_word(word),
+                Err(e) => {
+                    eprintln!("gorilla: (error) could not read {file_input}: {e}");
+                    break;
+                }
+            }
+        }
+
+        if words.skipped > 0 {
+            eprintln!(
+                "gorilla: (warning) skipped {} invalid lines from {file_input}",
+                words.skipped
+            );
+        }
     }
 
-    if let Some(file_input) = &gorilla.program_args.file_input {
-        eprintln!("gorilla: reading words from {}", file_input.purple());
+    // cloned, gorilla is borrowed mutably while the left wordlist is read
+    if let Some(combinator_input) = gorilla.program_args.combinator_input.clone() {
+        let (left_path, right_path) = (&combinator_input[0], &combinator_input[1]);
 
-        let file_input = File::open(file_input).unwrap();
-        let reader = BufReader::new(file_input);
-        let words_iter = reader.lines();
+        eprintln!(
+            "gorilla: combining words from {} and {}",
+            left_path.purple(),
+            right_path.purple()
+        );
 
-        for (_, l) in words_iter.enumerate() {
-            let line = l.unwrap();
-            gorilla.mutate_word(line);
+        let combinator = Combinator::new(
+            wordlist(right_path, gorilla.program_args.invalid_lines),
+            side_mutation_set(&gorilla.program_args.left_mutation_string),
+            &side_mutation_set(&gorilla.program_args.right_mutation_string),
+            gorilla.program_args.combinator_separators.clone(),
+        );
+
+        for word in wordlist(left_path, gorilla.program_args.invalid_lines) {
+            combinator.combine(&word, |combined| gorilla.mutate_word(combined));
+        }
+    }
+
+    if let Some(prince_input) = &gorilla.program_args.prince_input {
+        eprintln!("gorilla: chaining words from {}", prince_input.purple());
+
+        let chains = prince_iterator(
+            wordlist(prince_input, gorilla.program_args.invalid_lines).collect(),
+            PrinceOptions {
+                max_elements: gorilla.program_args.prince_elements,
+                min_length: gorilla.program_args.prince_min_length,
+                max_length: gorilla.program_args.prince_max_length,
+            },
+        );
+
+        eprintln!("gorilla: will generate {} chains", chains.calculate_total());
+
+        for chain in chains {
+            gorilla.mutate_word(chain);
         }
     }
 
@@ -209,6 +568,37 @@ fn main() {
         }
     }
 
+    if let Some(layout_name) = &gorilla.program_args.keywalk_input {
+        let layout = match Layout::from_string(layout_name) {
+            Some(layout) => layout,
+            None => {
+                eprintln!(
+                    "gorilla: (error) unknown keyboard layout: {layout_name}. Valid layouts: qwerty, azerty, qwertz"
+                );
+                panic!()
+            }
+        };
+
+        eprintln!(
+            "gorilla: generating keyboard walks on a {} layout",
+            layout.to_string().purple()
+        );
+
+        let keyboard = Keyboard::new(layout);
+        let walks = keyboard.walks(KeyWalkOptions {
+            min_length: gorilla.program_args.walk_min_length,
+            max_length: gorilla.program_args.walk_max_length,
+            max_turns: gorilla.program_args.walk_turns,
+            shift: gorilla.program_args.walk_shift,
+            max_shift_changes: gorilla.program_args.walk_shift_changes,
+            max_segments: gorilla.program_args.walk_segments,
+        });
+
+        for walk in walks {
+            gorilla.mutate_word(walk)
+        }
+    }
+
     if let Some(website) = &gorilla.program_args.website_input {
         eprintln!(
             "gorilla: scraping words from a website {}",
@@ -223,8 +613,15 @@ fn main() {
         }
     }
 
-    if gorilla.program_args.one_line {
-        println!()
+    gorilla.finish();
+
+    if gorilla.program_args.one_line && gorilla.program_args.file_save.is_none() {
+        gorilla.output.write_raw("\n").expect("write failed");
+    }
+
+    if let Err(e) = gorilla.output.finish() {
+        eprintln!("gorilla: (error) could not save output file: {e}");
+        panic!();
     }
 
     let end_time = SystemTime::now();
@@ -239,4 +636,25 @@ fn main() {
         gorilla.word_counter.to_string().red(),
         gorilla.mutation_counter.to_string().green()
     );
+
+    if !gorilla.filter.is_empty() {
+        eprintln!(
+            "         filtered out {} words",
+            gorilla.filtered_counter.to_string().yellow()
+        );
+    }
+
+    if gorilla.deduplicator.is_some() {
+        eprintln!(
+            "         dropped {} duplicate words",
+            gorilla.duplicate_counter.to_string().yellow()
+        );
+    }
+
+    if gorilla.hash_targets.is_some() {
+        eprintln!(
+            "         cracked {} words",
+            gorilla.cracked_counter.to_string().green()
+        );
+    }
 }
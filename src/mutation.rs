@@ -1,11 +1,13 @@
 use std::{
     cmp::Ordering,
     fmt::{self, Display},
-    fs,
-    io::Write,
 };
 
-use crate::patterns::{token_iterator, tokenize_format_string};
+use crate::{
+    output::OutputWriter,
+    patterns::{token_iterator, tokenize_format_string},
+    transliteration::{expand_umlauts, strip_diacritics, transliterate},
+};
 
 #[derive(Debug)]
 pub enum Action {
@@ -16,10 +18,18 @@ pub enum Action {
     FirstLetter,
     LowercaseAll,
     UppercaseAll,
+    Capitalize,
     RemoveFirstLetter,
     RemoveLastLetter,
     Reverse,
 
+    // names with accents or other scripts
+    StripDiacritics,
+    Transliterate,
+    ExpandUmlauts,
+    // every part becomes a separate word, the string holds the separators
+    Split(String),
+
     // more debugging related
     Clone,
     Wipe,
@@ -54,17 +64,37 @@ pub struct MutationResult {
     pub mutated_words: Vec<String>,
 }
 
+impl Display for MutationBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MutationBuildError::ActionDoesNotExist => write!(f, "action does not exist"),
+            MutationBuildError::MissingArguments => write!(f, "missing arguments"),
+            MutationBuildError::InvalidArgument(s) => write!(f, "invalid argument: {}", s),
+        }
+    }
+}
+
 impl MutationResult {
-    pub fn save_to_file(&self, file: &mut fs::File) {
+    pub fn save_to_file(&self, output: &mut OutputWriter, mutation_set: usize) {
         for mutated in &self.mutated_words {
-            let log_entry = format!("{}\n", mutated);
-            file.write_all(log_entry.as_bytes()).expect("write failed");
+            output
+                .write_candidate(mutated, &self.original_word, mutation_set)
+                .expect("write failed");
         }
     }
 }
 
 impl MutationSet {
-    pub fn perform(&self, mutation_result: &mut MutationResult, word: &str) {
+    pub fn describe(&self) -> String {
+        self.mutations
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<String>>()
+            .join(" -> ")
+    }
+
+    // the words generated from a word by the whole set
+    pub fn mutate(&self, word: &str) -> Vec<String> {
         let mut result: Vec<String> = vec![word.to_owned()];
 
         for mutation in &self.mutations {
@@ -75,7 +105,7 @@ impl MutationSet {
             result = new_result
         }
 
-        mutation_result.mutated_words = result
+        result
     }
 
     pub fn empty_set() -> MutationSet {
@@ -90,6 +120,14 @@ impl MutationSet {
     }
 }
 
+// only the first character is uppercased, ß becomes SS
+pub fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map_or(String::new(), |ch| ch.to_uppercase().chain(chars).collect())
+}
+
 impl Mutation {
     pub fn perform(&self, result: &mut Vec<String>, input: &str) {
         if self.keep_original {
@@ -145,10 +183,21 @@ impl Mutation {
             Action::Reverse => result.push(input.chars().rev().collect()),
             Action::UppercaseAll => result.push(input.to_uppercase()),
             Action::LowercaseAll => result.push(input.to_lowercase()),
+            Action::Capitalize => result.push(capitalize(input)),
             Action::Clone => result.append(&mut vec![input.to_owned(), input.to_owned()]),
             Action::Wipe => result.push(String::new()),
             Action::Nothing => result.push(input.to_owned()),
             Action::Remove => (),
+            Action::StripDiacritics => result.push(strip_diacritics(input)),
+            Action::Transliterate => result.push(transliterate(input)),
+            Action::ExpandUmlauts => result.push(expand_umlauts(input)),
+            Action::Split(separators) => {
+                for part in input.split(|ch| separators.contains(ch)) {
+                    if !part.is_empty() {
+                        result.push(part.to_owned())
+                    }
+                }
+            }
         }
     }
 }
@@ -172,7 +221,12 @@ impl Display for Mutation {
             Action::FirstLetter => write!(f, "first letter"),
             Action::UppercaseAll => write!(f, "uppercase all"),
             Action::LowercaseAll => write!(f, "lowercase all"),
+            Action::Capitalize => write!(f, "capitalize"),
             Action::Remove => write!(f, "remove"),
+            Action::StripDiacritics => write!(f, "strip diacritics"),
+            Action::Transliterate => write!(f, "transliterate"),
+            Action::ExpandUmlauts => write!(f, "expand umlauts"),
+            Action::Split(separators) => write!(f, "split on {:?}", separators),
 
             Action::IfCharacterLength(not, ord, number) => {
                 write!(f, "if length {:?} {} = {}", ord, number, !not)
@@ -265,9 +319,20 @@ impl Action {
             "nothing" => Ok(Action::Nothing),
             "uppercase_all" => Ok(Action::UppercaseAll),
             "lowercase_all" => Ok(Action::LowercaseAll),
+            "capitalize" => Ok(Action::Capitalize),
             "remove_last_letter" => Ok(Action::RemoveLastLetter),
             "remove_first_letter" => Ok(Action::RemoveFirstLetter),
             "remove" => Ok(Action::Remove),
+            "strip_diacritics" => Ok(Action::StripDiacritics),
+            "transliterate" => Ok(Action::Transliterate),
+            "expand_umlauts" => Ok(Action::ExpandUmlauts),
+            // spaces and hyphens by default
+            "split" => Ok(Action::Split(
+                arguments
+                    .first()
+                    .filter(|s| !s.is_empty())
+                    .map_or(String::from(" -"), |s| s.to_string()),
+            )),
             _ => Err(MutationBuildError::ActionDoesNotExist),
         }
     }
@@ -277,11 +342,7 @@ pub fn parse_mutation_string(mutation_strings: &Vec<String>) -> Vec<Mutation> {
     let mut mutations: Vec<Mutation> = vec![];
 
     for mutation_string in mutation_strings {
-        let mut mutation_split: Vec<&str> = mutation_string
-            .split(':')
-            // .into_iter()
-            .map(|x| x.trim())
-            .collect();
+        let mut mutation_split: Vec<&str> = mutation_string.split(':').collect();
         let mut mutation_action = mutation_split[0].trim();
         let mut mutation_runtimes: usize = 1;
         let mut mutation_options: &str = "";
@@ -302,6 +363,11 @@ pub fn parse_mutation_string(mutation_strings: &Vec<String>) -> Vec<Mutation> {
 
         mutation_split.remove(0);
 
+        // the separators of split can be spaces
+        if mutation_action != "split" {
+            mutation_split = mutation_split.into_iter().map(str::trim).collect();
+        }
+
         match Action::from_string(mutation_action, mutation_split, mutation_options) {
             Ok(m) => mutations.push(Mutation {
                 action: m,
@@ -309,7 +375,7 @@ pub fn parse_mutation_string(mutation_strings: &Vec<String>) -> Vec<Mutation> {
                 keep_original: mutation_options.contains('k'),
             }),
             Err(e) => eprintln!(
-                "warning: couldn't build mutation {} ({:?})",
+                "warning: couldn't build mutation {} ({})",
                 mutation_action, e
             ),
         }
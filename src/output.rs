@@ -0,0 +1,388 @@
+use std::{
+    borrow::Cow,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    arguments::OutputFormat,
+    compression::{compress_writer, CompressedWriter, Compression},
+    encoding::{encode_hex_notation, encode_word, unescape_bytes},
+    hashing::HashAlgorithm,
+};
+
+const OUTPUT_BUFFER_SIZE: usize = 1 << 16;
+
+// every generated word goes through this writer, whether it's saved
+// to a file or printed to stdout
+pub struct OutputWriter {
+    writer: BufWriter<CompressedWriter>,
+    separator: String,
+    hex_output: bool,
+    split: Option<Split>,
+    mode: WriteMode,
+    // files that are moved into place by finish
+    pending: Vec<PendingFile>,
+    // every word is followed by its hashes: word:hash1:hash2
+    pub hash_algorithms: Vec<HashAlgorithm>,
+    pub format: OutputFormat,
+    // description of every mutation set, used by the jsonl format
+    pub mutation_chains: Vec<String>,
+}
+
+// what happens when the output file already exists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WriteMode {
+    // add the words to the end of the file
+    Append,
+    // replace the file once every word is written
+    Overwrite,
+    // refuse to touch the file
+    NoClobber,
+}
+
+// written next to the target and renamed when the output is complete, so a
+// failed run never leaves a truncated file behind. the temp file isn't removed
+// if gorilla is killed (ctrl+c)
+struct PendingFile {
+    temp_path: PathBuf,
+    path: PathBuf,
+    mode: WriteMode,
+}
+
+#[derive(Default)]
+pub struct SplitOptions {
+    pub max_words: Option<u64>,
+    // counted before compression
+    pub max_bytes: Option<u64>,
+    // compress every chunk, the extension is added to the file names
+    pub compression: Option<Compression>,
+}
+
+struct Split {
+    path: String,
+    options: SplitOptions,
+    chunk: usize,
+    chunk_words: u64,
+    chunk_bytes: u64,
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+}
+
+impl PendingFile {
+    fn create(path: &str, mode: WriteMode) -> io::Result<(File, PendingFile)> {
+        let path = PathBuf::from(path);
+        if mode == WriteMode::NoClobber && path.exists() {
+            return Err(already_exists(&path));
+        }
+
+        // same directory as the target, renaming across file systems isn't atomic
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+        let file = File::create(&temp_path)?;
+
+        Ok((
+            file,
+            PendingFile {
+                temp_path,
+                path,
+                mode,
+            },
+        ))
+    }
+
+    fn commit(&self) -> io::Result<()> {
+        match self.mode {
+            // fails if the file was created while the words were generated,
+            // rename would replace it
+            WriteMode::NoClobber => {
+                fs::hard_link(&self.temp_path, &self.path).map_err(|e| match e.kind() {
+                    io::ErrorKind::AlreadyExists => already_exists(&self.path),
+                    _ => e,
+                })
+            }
+            _ => fs::rename(&self.temp_path, &self.path),
+        }
+        // the temp file is removed when this is dropped
+    }
+}
+
+impl Drop for PendingFile {
+    // nothing is left behind if the output wasn't finished because of an error
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}
+
+// fifos, devices (/dev/null) and symlinks are written to directly, renaming
+// a temp file over them would replace them with a regular file
+fn is_regular_file(path: &str) -> bool {
+    fs::symlink_metadata(path).map_or(true, |m| m.file_type().is_file())
+}
+
+fn open_file(
+    path: &str,
+    compression: Compression,
+    mode: WriteMode,
+) -> io::Result<(CompressedWriter, Option<PendingFile>)> {
+    let (file, pending) = match mode {
+        WriteMode::Append => (
+            OpenOptions::new().create(true).append(true).open(path)?,
+            None,
+        ),
+        WriteMode::Overwrite if !is_regular_file(path) => (File::create(path)?, None),
+        WriteMode::Overwrite | WriteMode::NoClobber => {
+            let (file, pending) = PendingFile::create(path, mode)?;
+            (file, Some(pending))
+        }
+    };
+
+    Ok((compress_writer(Box::new(file), compression)?, pending))
+}
+
+// flushes the buffer and writes the trailer of the compressed stream
+fn finish_writer(writer: BufWriter<CompressedWriter>) -> io::Result<()> {
+    writer.into_inner().map_err(|e| e.into_error())?.finish()
+}
+
+fn compression_extension(compression: Compression) -> &'static str {
+    match compression {
+        Compression::None => "",
+        Compression::Gzip => ".gz",
+        Compression::Zstd => ".zst",
+        Compression::Bzip2 => ".bz2",
+        Compression::Xz => ".xz",
+    }
+}
+
+// words.txt.gz -> words.0001.txt.gz
+pub fn chunk_path(path: &str, chunk: usize, compression: Option<Compression>) -> String {
+    let compression = compression.unwrap_or(Compression::from_extension(path));
+    let compression_ext = compression_extension(compression);
+
+    let path = Path::new(path);
+    let mut file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    // the compression extension goes after the chunk number
+    let has_compression_ext = file_name.len() >= compression_ext.len()
+        && file_name.as_bytes()[file_name.len() - compression_ext.len()..]
+            .eq_ignore_ascii_case(compression_ext.as_bytes());
+    if has_compression_ext {
+        file_name.truncate(file_name.len() - compression_ext.len());
+    }
+
+    let file_name = Path::new(&file_name);
+    let stem = file_name.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = file_name
+        .extension()
+        .map_or(String::new(), |ext| format!(".{}", ext.to_string_lossy()));
+
+    path.with_file_name(format!("{stem}.{chunk:04}{ext}{compression_ext}"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl Split {
+    fn open_chunk(
+        &mut self,
+        mode: WriteMode,
+    ) -> io::Result<(String, CompressedWriter, Option<PendingFile>)> {
+        self.chunk += 1;
+        self.chunk_words = 0;
+        self.chunk_bytes = 0;
+
+        let path = chunk_path(&self.path, self.chunk, self.options.compression);
+        let compression = Compression::from_extension(&path);
+        let (writer, pending) = open_file(&path, compression, mode)?;
+
+        Ok((path, writer, pending))
+    }
+}
+
+impl OutputWriter {
+    pub fn new(writer: Box<dyn Write>, separator: &str, hex_output: bool) -> OutputWriter {
+        OutputWriter::with_writer(CompressedWriter::None(writer), separator, hex_output)
+    }
+
+    fn with_writer(writer: CompressedWriter, separator: &str, hex_output: bool) -> OutputWriter {
+        OutputWriter {
+            writer: BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, writer),
+            separator: separator.to_owned(),
+            hex_output,
+            split: None,
+            mode: WriteMode::Append,
+            pending: Vec::new(),
+            hash_algorithms: Vec::new(),
+            format: OutputFormat::Plain,
+            mutation_chains: Vec::new(),
+        }
+    }
+
+    pub fn stdout(separator: &str, hex_output: bool) -> OutputWriter {
+        OutputWriter::new(Box::new(io::stdout().lock()), separator, hex_output)
+    }
+
+    // compressed if the path ends with a known extension
+    pub fn file(
+        path: &str,
+        separator: &str,
+        hex_output: bool,
+        mode: WriteMode,
+    ) -> io::Result<OutputWriter> {
+        let (writer, pending) = open_file(path, Compression::from_extension(path), mode)?;
+
+        let mut output = OutputWriter::with_writer(writer, separator, hex_output);
+        output.mode = mode;
+        output.pending.extend(pending);
+        Ok(output)
+    }
+
+    // rolls over into numbered files (see chunk_path) after the limits are reached
+    pub fn split_files(
+        path: &str,
+        separator: &str,
+        hex_output: bool,
+        mode: WriteMode,
+        options: SplitOptions,
+    ) -> io::Result<OutputWriter> {
+        let mut split = Split {
+            path: path.to_owned(),
+            options,
+            chunk: 0,
+            chunk_words: 0,
+            chunk_bytes: 0,
+        };
+
+        let (chunk_path, writer, pending) = split.open_chunk(mode)?;
+        eprintln!("gorilla: writing to {chunk_path}");
+
+        let mut output = OutputWriter::with_writer(writer, separator, hex_output);
+        output.split = Some(split);
+        output.mode = mode;
+        output.pending.extend(pending);
+        Ok(output)
+    }
+
+    pub fn write_word(&mut self, word: &str) -> io::Result<()> {
+        // words containing the separator could not be told apart from the next word
+        let mut line = if word.contains(&self.separator) {
+            Cow::Owned(encode_hex_notation(&unescape_bytes(word)))
+        } else {
+            encode_word(word, self.hex_output)
+        };
+
+        for algorithm in &self.hash_algorithms {
+            let line = line.to_mut();
+            line.push(':');
+            line.push_str(&algorithm.hash(word));
+        }
+
+        self.write_record(&line)
+    }
+
+    // a word or a json line, counted for the split limits
+    fn write_record(&mut self, record: &str) -> io::Result<()> {
+        // json lines are always separated by a new line
+        let separator = match self.format {
+            OutputFormat::Jsonl => "\n",
+            OutputFormat::Plain => &self.separator,
+        };
+
+        if let Some(split) = &mut self.split {
+            let length = (record.len() + separator.len()) as u64;
+            let full = split
+                .options
+                .max_words
+                .is_some_and(|max| split.chunk_words >= max)
+                || split
+                    .options
+                    .max_bytes
+                    .is_some_and(|max| split.chunk_bytes + length > max);
+
+            if full && split.chunk_words > 0 {
+                let (chunk_path, writer, pending) = split.open_chunk(self.mode)?;
+                eprintln!("gorilla: writing to {chunk_path}");
+                self.pending.extend(pending);
+
+                let previous = mem::replace(
+                    &mut self.writer,
+                    BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, writer),
+                );
+                finish_writer(previous)?;
+            }
+
+            split.chunk_words += 1;
+            split.chunk_bytes += length;
+        }
+
+        self.writer.write_all(record.as_bytes())?;
+        self.writer.write_all(separator.as_bytes())
+    }
+
+    // a generated word along with where it comes from
+    pub fn write_candidate(
+        &mut self,
+        word: &str,
+        original_word: &str,
+        mutation_set: usize,
+    ) -> io::Result<()> {
+        if self.format == OutputFormat::Plain {
+            return self.write_word(word);
+        }
+
+        let mut record = json!({
+            "candidate": encode_word(word, self.hex_output),
+            "original": encode_word(original_word, self.hex_output),
+            "mutation_set": mutation_set,
+            "mutations": self.mutation_chains.get(mutation_set),
+        });
+
+        if !self.hash_algorithms.is_empty() {
+            let hashes: serde_json::Map<String, Value> = self
+                .hash_algorithms
+                .iter()
+                .map(|a| (a.to_string(), Value::from(a.hash(word))))
+                .collect();
+            record["hashes"] = Value::Object(hashes);
+        }
+
+        self.write_record(&record.to_string())
+    }
+
+    pub fn write_raw(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    // finishes the output and moves the files written in overwrite/no-clobber mode into place
+    pub fn finish(&mut self) -> io::Result<()> {
+        let writer = mem::replace(
+            &mut self.writer,
+            BufWriter::new(CompressedWriter::None(Box::new(io::sink()))),
+        );
+        // nothing is committed if the compressed stream couldn't be finished
+        finish_writer(writer)?;
+
+        for file in self.pending.drain(..) {
+            file.commit()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::mutation::{MutationResult, MutationSet};
+
+// amount of input words sent to a worker at once
+pub const BATCH_SIZE: usize = 4096;
+
+// batches (per thread) that can be queued or waiting to be written, so a slow
+// batch can't make the results pile up in memory
+const BATCHES_PER_THREAD: usize = 4;
+
+// index of the mutation set and its result
+pub type SetResult = (usize, MutationResult);
+
+struct Batch {
+    sequence: u64,
+    words: Vec<String>,
+}
+
+/*
+input words are batched and sent to the workers, which apply every mutation set
+to them. the mutated words are sent back to the thread that owns the pool, so the
+output (and deduplication) still happens in a single place.
+*/
+pub struct WorkerPool {
+    job_sender: Option<SyncSender<Batch>>,
+    result_receiver: Receiver<(u64, Vec<SetResult>)>,
+    workers: Vec<JoinHandle<()>>,
+    ordered: bool,
+    next_sequence: u64,
+    // results waiting for the batches before them when the output is ordered
+    pending: BTreeMap<u64, Vec<SetResult>>,
+    max_pending: usize,
+    next_output: u64,
+}
+
+// every mutation set applied to a word, used with and without the pool
+pub fn mutate_word(mutation_sets: &[MutationSet], word: &str) -> Vec<SetResult> {
+    mutation_sets
+        .iter()
+        .enumerate()
+        .map(|(set_index, mutation_set)| {
+            let mutation_result = MutationResult {
+                original_word: word.to_owned(),
+                mutated_words: mutation_set.mutate(word),
+            };
+            (set_index, mutation_result)
+        })
+        .collect()
+}
+
+fn mutate_batch(mutation_sets: &[MutationSet], words: Vec<String>) -> Vec<SetResult> {
+    words
+        .iter()
+        .flat_map(|word| mutate_word(mutation_sets, word))
+        .collect()
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize, mutation_sets: Arc<Vec<MutationSet>>, ordered: bool) -> WorkerPool {
+        let (job_sender, job_receiver) = mpsc::sync_channel::<Batch>(threads * 2);
+        let (result_sender, result_receiver) = mpsc::sync_channel(threads * 2);
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..threads)
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender: SyncSender<(u64, Vec<SetResult>)> = result_sender.clone();
+                let mutation_sets = Arc::clone(&mutation_sets);
+
+                thread::spawn(move || loop {
+                    let batch = match job_receiver.lock().unwrap().recv() {
+                        Ok(batch) => batch,
+                        Err(_) => return,
+                    };
+
+                    let mutated = mutate_batch(&mutation_sets, batch.words);
+                    if result_sender.send((batch.sequence, mutated)).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        WorkerPool {
+            job_sender: Some(job_sender),
+            result_receiver,
+            workers,
+            ordered,
+            next_sequence: 0,
+            pending: BTreeMap::new(),
+            max_pending: threads * BATCHES_PER_THREAD,
+            next_output: 0,
+        }
+    }
+
+    // blocks when every worker is busy, returns the results that are ready
+    pub fn submit(&mut self, words: Vec<String>) -> Vec<Vec<SetResult>> {
+        let mut results = Vec::new();
+
+        // wait for the batch everyone is waiting for before sending more
+        while self.pending.len() >= self.max_pending {
+            self.receive(&mut results);
+        }
+
+        let mut batch = Batch {
+            sequence: self.next_sequence,
+            words,
+        };
+        self.next_sequence += 1;
+
+        let job_sender = self.job_sender.clone().expect("pool already finished");
+        loop {
+            match job_sender.try_send(batch) {
+                Ok(()) => break,
+                // the workers could be waiting for their results to be received
+                Err(TrySendError::Full(full_batch)) => {
+                    batch = full_batch;
+                    self.receive(&mut results);
+                }
+                Err(TrySendError::Disconnected(_)) => panic!("all workers stopped"),
+            }
+        }
+
+        while let Ok(result) = self.result_receiver.try_recv() {
+            self.collect(result, &mut results);
+        }
+        results
+    }
+
+    fn receive(&mut self, results: &mut Vec<Vec<SetResult>>) {
+        let result = self.result_receiver.recv().expect("all workers stopped");
+        self.collect(result, results);
+    }
+
+    // waits for the workers to finish and returns the remaining results
+    pub fn finish(&mut self) -> Vec<Vec<SetResult>> {
+        self.job_sender = None;
+
+        let mut results = Vec::new();
+        while let Ok(result) = self.result_receiver.recv() {
+            self.collect(result, &mut results);
+        }
+
+        for worker in self.workers.drain(..) {
+            worker.join().expect("worker panicked");
+        }
+
+        results
+    }
+
+    fn collect(
+        &mut self,
+        (sequence, words): (u64, Vec<SetResult>),
+        results: &mut Vec<Vec<SetResult>>,
+    ) {
+        if !self.ordered {
+            results.push(words);
+            return;
+        }
+
+        self.pending.insert(sequence, words);
+        while let Some(words) = self.pending.remove(&self.next_output) {
+            results.push(words);
+            self.next_output += 1;
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+/*
+chains words from a wordlist (PRINCE attack). the words are grouped by length
+and a chain is a list of element lengths that fits the length range, like
+[4, 4, 1] for password1. only the words of the right length are tried for each
+element, so nothing is generated just to be thrown away.
+
+chains with the smallest keyspace (and fewest elements) are generated first,
+and words at the top of the list come first within a chain, as they are
+considered more probable.
+*/
+
+pub struct PrinceOptions {
+    pub max_elements: usize,
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+pub struct PrinceIter {
+    // words of every length (index) in wordlist order
+    buckets: Vec<Vec<String>>,
+    // element lengths of every chain
+    chains: Vec<Vec<usize>>,
+    chain: usize,
+    // word of every element within its bucket
+    indexes: Vec<usize>,
+}
+
+// every combination of element lengths whose total fits the length range
+fn find_chains(
+    buckets: &[Vec<String>],
+    options: &PrinceOptions,
+    chain: &mut Vec<usize>,
+    length: usize,
+    chains: &mut Vec<Vec<usize>>,
+) {
+    if !chain.is_empty() && length >= options.min_length {
+        chains.push(chain.clone());
+    }
+
+    if chain.len() == options.max_elements {
+        return;
+    }
+
+    for element_length in 1..=options.max_length - length {
+        // lengths without words would only produce empty chains
+        if buckets[element_length].is_empty() {
+            continue;
+        }
+
+        chain.push(element_length);
+        find_chains(buckets, options, chain, length + element_length, chains);
+        chain.pop();
+    }
+}
+
+fn keyspace(buckets: &[Vec<String>], chain: &[usize]) -> u128 {
+    chain.iter().fold(1u128, |total, l| {
+        total.saturating_mul(buckets[*l].len() as u128)
+    })
+}
+
+pub fn prince_iterator(words: Vec<String>, options: PrinceOptions) -> PrinceIter {
+    let mut buckets: Vec<Vec<String>> = vec![Vec::new(); options.max_length + 1];
+    let mut seen = HashSet::new();
+
+    for word in words {
+        let length = word.chars().count();
+        if length > 0 && length <= options.max_length && seen.insert(word.to_owned()) {
+            buckets[length].push(word);
+        }
+    }
+
+    let mut chains = Vec::new();
+    find_chains(&buckets, &options, &mut Vec::new(), 0, &mut chains);
+    chains.sort_by_cached_key(|chain| (keyspace(&buckets, chain), chain.len()));
+
+    PrinceIter {
+        indexes: vec![0; chains.first().map_or(0, Vec::len)],
+        buckets,
+        chains,
+        chain: 0,
+    }
+}
+
+impl PrinceIter {
+    pub fn calculate_total(&self) -> u128 {
+        self.chains.iter().fold(0u128, |total, chain| {
+            total.saturating_add(keyspace(&self.buckets, chain))
+        })
+    }
+}
+
+impl Iterator for PrinceIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chain = self.chains.get(self.chain)?;
+
+        let candidate = chain
+            .iter()
+            .zip(&self.indexes)
+            .map(|(length, index)| self.buckets[*length][*index].as_str())
+            .collect();
+
+        // the last element changes first
+        for position in (0..chain.len()).rev() {
+            self.indexes[position] += 1;
+            if self.indexes[position] < self.buckets[chain[position]].len() {
+                return Some(candidate);
+            }
+            self.indexes[position] = 0;
+        }
+
+        self.chain += 1;
+        if let Some(chain) = self.chains.get(self.chain) {
+            self.indexes = vec![0; chain.len()];
+        }
+
+        Some(candidate)
+    }
+}
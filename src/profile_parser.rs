@@ -0,0 +1,145 @@
+use std::{collections::HashMap, fs};
+
+use colored::Colorize;
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::{Marker, TScalarStyle},
+    yaml::Hash,
+    Yaml,
+};
+
+use crate::formatting::{push_answer, FormatFieldAnswer};
+
+// --field name=value, the same name can be used more than once
+pub fn push_field_answers(answers: &mut Vec<FormatFieldAnswer>, fields: &[(String, String)]) {
+    for (name, value) in fields {
+        push_answer(answers, name, value);
+    }
+}
+
+/*
+a profile is a map of field names to answers, the answers can be lists:
+
+f_name: john
+pets: [ rex, fluffy ]
+
+the file can also contain a list of profiles. json is valid yaml so both formats
+are read the same way
+*/
+pub fn fmt_answers_from_profile(profile_path: &str) -> Result<Vec<Vec<FormatFieldAnswer>>, String> {
+    let input = fs::read_to_string(profile_path).map_err(|e| e.to_string())?;
+
+    eprintln!("gorilla: parsing {} profile file", profile_path.purple());
+
+    let mut loader = RawLoader::default();
+    Parser::new(input.chars())
+        .load(&mut loader, false)
+        .map_err(|e| e.to_string())?;
+
+    match loader.docs.first() {
+        Some(Yaml::Array(profiles)) => profiles.iter().map(profile_answers).collect(),
+        Some(profile) => Ok(vec![profile_answers(profile)?]),
+        None => Ok(Vec::new()),
+    }
+}
+
+/*
+builds the document like YamlLoader, but the scalars are kept as they were
+written. YamlLoader reads zip: 01234 as the number 1234 and dob: 01021990 as
+1021990
+*/
+#[derive(Default)]
+struct RawLoader {
+    docs: Vec<Yaml>,
+    // open lists and maps with their anchor and the key waiting for a value
+    stack: Vec<(Yaml, usize, Option<Yaml>)>,
+    anchors: HashMap<usize, Yaml>,
+}
+
+impl RawLoader {
+    fn insert(&mut self, node: Yaml, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+
+        match self.stack.last_mut() {
+            Some((Yaml::Array(values), _, _)) => values.push(node),
+            Some((Yaml::Hash(map), _, key)) => match key.take() {
+                Some(key) => {
+                    map.insert(key, node);
+                }
+                None => *key = Some(node),
+            },
+            _ => self.docs.push(node),
+        }
+    }
+}
+
+impl MarkedEventReceiver for RawLoader {
+    fn on_event(&mut self, event: Event, _: Marker) {
+        match event {
+            Event::SequenceStart(anchor) => {
+                self.stack.push((Yaml::Array(Vec::new()), anchor, None))
+            }
+            Event::MappingStart(anchor) => self.stack.push((Yaml::Hash(Hash::new()), anchor, None)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some((node, anchor, _)) = self.stack.pop() {
+                    self.insert(node, anchor)
+                }
+            }
+            Event::Scalar(value, style, anchor, _) => {
+                let node = match value.as_str() {
+                    "~" | "null" | "Null" | "NULL" if style == TScalarStyle::Plain => Yaml::Null,
+                    _ => Yaml::String(value),
+                };
+                self.insert(node, anchor)
+            }
+            Event::Alias(anchor) => {
+                let node = self.anchors.get(&anchor).cloned().unwrap_or(Yaml::BadValue);
+                self.insert(node, 0)
+            }
+            _ => (),
+        }
+    }
+}
+
+fn profile_answers(profile: &Yaml) -> Result<Vec<FormatFieldAnswer>, String> {
+    let Yaml::Hash(fields) = profile else {
+        return Err(String::from(
+            "a profile has to be a map of field names to answers",
+        ));
+    };
+
+    let mut answers: Vec<FormatFieldAnswer> = Vec::new();
+
+    for (name, answer) in fields {
+        let name = scalar(name).ok_or(format!("invalid field name {name:?}"))?;
+        let mut field_answer = FormatFieldAnswer::new(&name, "");
+
+        match answer {
+            Yaml::Array(values) => {
+                for value in values {
+                    let value = scalar(value).ok_or(format!("invalid value for {name}"))?;
+                    field_answer.push_value(&value)
+                }
+            }
+            // null means the field isn't answered
+            Yaml::Null => (),
+            answer => {
+                let value = scalar(answer).ok_or(format!("invalid value for {name}"))?;
+                field_answer.push_values(&value)
+            }
+        }
+
+        answers.push(field_answer)
+    }
+
+    Ok(answers)
+}
+
+fn scalar(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) => Some(s.to_owned()),
+        _ => None,
+    }
+}
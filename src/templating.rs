@@ -0,0 +1,146 @@
+use crate::{
+    dates::{Date, DATE_COMPONENTS},
+    mutation::capitalize,
+    transliteration::{strip_diacritics, transliterate},
+};
+
+/*
+the text between the braces of a formatting placeholder:
+
+{f_name|first:2|upper}
+ ^^^^^^ field, followed by the functions applied from left to right
+{dob.yy}
+     ^^ part of a date field
+*/
+pub struct Placeholder<'a> {
+    pub field: &'a str,
+    pub component: Option<&'a str>,
+    pub functions: Vec<(&'a str, Option<&'a str>)>,
+}
+
+// functions that don't take arguments and work on every field
+const TEXT_FUNCTIONS: [&str; 7] = [
+    "upper",
+    "lower",
+    "capitalize",
+    "initials",
+    "reverse",
+    "transliterate",
+    "strip_diacritics",
+];
+
+// the date component used by the function, {dob|year} is {dob.yyyy}
+fn date_component(function: &str) -> Option<&str> {
+    match function {
+        "year" => Some("yyyy"),
+        "month" => Some("mm"),
+        "day" => Some("dd"),
+        _ => DATE_COMPONENTS.iter().find(|c| **c == function).copied(),
+    }
+}
+
+fn parse_count(function: &str, argument: Option<&str>) -> Result<usize, String> {
+    argument
+        .and_then(|a| a.parse().ok())
+        .ok_or(format!("{function} needs a number, like {function}:2"))
+}
+
+impl Placeholder<'_> {
+    pub fn parse(placeholder: &str) -> Placeholder<'_> {
+        let mut segments = placeholder.split('|').map(str::trim);
+        let name = segments.next().unwrap_or_default();
+
+        let (field, component) = match name.split_once('.') {
+            Some((field, component)) => (field, Some(component)),
+            None => (name, None),
+        };
+
+        Placeholder {
+            field,
+            component,
+            functions: segments
+                .map(|f| match f.split_once(':') {
+                    Some((function, argument)) => (function, Some(argument)),
+                    None => (f, None),
+                })
+                .collect(),
+        }
+    }
+
+    // whether the functions and the date component can be used for the field
+    pub fn check(&self, date_field: bool) -> Result<(), String> {
+        if let Some(component) = self.component {
+            if !date_field || !DATE_COMPONENTS.contains(&component) {
+                return Err(format!("{} has no {component}", self.field));
+            }
+        }
+
+        for &(function, argument) in &self.functions {
+            match function {
+                "first" | "last" => {
+                    parse_count(function, argument)?;
+                }
+                _ if TEXT_FUNCTIONS.contains(&function) => (),
+                _ if date_field && date_component(function).is_some() => (),
+                _ if date_component(function).is_some() => {
+                    return Err(format!("{function} can only be used for date fields"))
+                }
+                _ => return Err(format!("unknown function {function}")),
+            }
+        }
+
+        Ok(())
+    }
+
+    // the date is parsed from the value for date fields
+    pub fn fill(&self, value: &str, date: Option<&Date>) -> String {
+        let mut result = match self.component {
+            Some(component) => date
+                .and_then(|d| d.component(component))
+                .unwrap_or_default(),
+            None => value.to_owned(),
+        };
+
+        for &(function, argument) in &self.functions {
+            result = apply_function(&result, function, argument, date);
+        }
+
+        result
+    }
+}
+
+// unknown functions (reported when the formatting sets are loaded) leave the value as it is
+fn apply_function(
+    value: &str,
+    function: &str,
+    argument: Option<&str>,
+    date: Option<&Date>,
+) -> String {
+    match function {
+        "first" => {
+            let count = parse_count(function, argument).unwrap_or(usize::MAX);
+            value.chars().take(count).collect()
+        }
+        "last" => {
+            let count = parse_count(function, argument).unwrap_or(usize::MAX);
+            let skip = value.chars().count().saturating_sub(count);
+            value.chars().skip(skip).collect()
+        }
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        "capitalize" => capitalize(value),
+        // Acme Widget-Works -> AWW
+        "initials" => value
+            .split(|ch: char| ch.is_whitespace() || ch == '-')
+            .filter_map(|word| word.chars().next())
+            .collect(),
+        "reverse" => value.chars().rev().collect(),
+        "transliterate" => transliterate(value),
+        "strip_diacritics" => strip_diacritics(value),
+        _ => match (date_component(function), date) {
+            (Some(component), Some(date)) => date.component(component).unwrap_or_default(),
+            (Some(_), None) => String::new(),
+            _ => value.to_owned(),
+        },
+    }
+}
@@ -33,15 +33,10 @@ mod token_tests {
 
 #[cfg(test)]
 mod mutation_tests {
-    use crate::mutation::{Action, Mutation, MutationResult, MutationSet};
+    use crate::mutation::{parse_mutation_string, Action, Mutation, MutationSet};
 
     #[test]
     fn basic_mutations() {
-        let mut mutation_result = MutationResult {
-            original_word: String::from("word"),
-            mutated_words: vec![],
-        };
-
         let mutation_set = MutationSet {
             mutations: vec![
                 Mutation {
@@ -62,18 +57,11 @@ mod mutation_tests {
             ],
         };
 
-        mutation_set.perform(&mut mutation_result, "word");
-
-        assert_eq!(mutation_result.mutated_words, vec!["abcdrowabc"])
+        assert_eq!(mutation_set.mutate("word"), vec!["abcdrowabc"])
     }
 
     #[test]
     fn advanced_mutation() {
-        let mut mutation_result = MutationResult {
-            original_word: String::from("word"),
-            mutated_words: vec![],
-        };
-
         let mutation_set = MutationSet {
             mutations: vec![Mutation {
                 action: Action::Append(String::from("{0-9}")),
@@ -82,38 +70,347 @@ mod mutation_tests {
             }],
         };
 
-        mutation_set.perform(&mut mutation_result, "word");
-
         assert_eq!(
-            mutation_result.mutated_words,
+            mutation_set.mutate("word"),
             vec![
                 "word0", "word1", "word2", "word3", "word4", "word5", "word6", "word7", "word8",
                 "word9"
             ]
         )
     }
+
+    #[test]
+    fn capitalize() {
+        let mutation_set = MutationSet {
+            mutations: vec![Mutation {
+                action: Action::Capitalize,
+                times: 1,
+                keep_original: false,
+            }],
+        };
+
+        for (word, expected) in [
+            ("john", "John"),
+            ("", ""),
+            ("émile", "Émile"),
+            ("łukasz", "Łukasz"),
+            ("ßen", "SSen"),
+            ("1st", "1st"),
+        ] {
+            assert_eq!(mutation_set.mutate(word), vec![expected]);
+        }
+    }
+
+    #[test]
+    fn name_mutations() {
+        let perform = |mutations: &[&str], word: &str| {
+            let mutation_set = MutationSet {
+                mutations: parse_mutation_string(
+                    &mutations.iter().map(|m| m.to_string()).collect(),
+                ),
+            };
+            mutation_set.mutate(word)
+        };
+
+        let name = "Zoë Müller-Łukasiewicz";
+        assert_eq!(
+            perform(&["strip_diacritics"], name),
+            vec!["Zoe Muller-Lukasiewicz"]
+        );
+        assert_eq!(
+            perform(&["expand_umlauts"], name),
+            vec!["Zoë Mueller-Łukasiewicz"]
+        );
+        assert_eq!(
+            perform(&["transliterate"], "Сергей Ødegård"),
+            vec!["Sergei Odegard"]
+        );
+        assert_eq!(
+            perform(&["split", "transliterate", "lowercase_all"], name),
+            vec!["zoe", "muller", "lukasiewicz"]
+        );
+        assert_eq!(perform(&["split:_"], "a_b-c"), vec!["a", "b-c"]);
+        assert_eq!(perform(&["split: "], "a b-c"), vec!["a", "b-c"]);
+        assert_eq!(perform(&["split:"], "a b-c"), vec!["a", "b", "c"]);
+    }
 }
 
 #[cfg(test)]
 mod yaml_test {
-    use crate::{mutation::MutationResult, yaml_parser::get_mutation_sets};
+    use crate::yaml_parser::get_mutation_sets;
 
     #[test]
     fn yaml_parse_test() {
-        let mut mutation_result = MutationResult {
-            original_word: String::from("word"),
-            mutated_words: vec![],
-        };
-
         let mutation_sets = get_mutation_sets(
             "name: alphabet
 mutation_sets:
   - [ wipe, \"append:{a-z}\" ] # => a, b, c, ..., z",
         );
 
-        mutation_sets[0].perform(&mut mutation_result, "word");
+        assert_eq!(mutation_sets[0].mutate("word").len(), 26);
+    }
+}
+
+#[cfg(test)]
+mod formatting_tests {
+    use crate::{
+        built_in_sets, dates::Date, formatting::FormatFieldAnswer,
+        yaml_parser::parse_formatting_yaml,
+    };
+
+    const FORMATS: &str = r#"
+name: test
+fields:
+  - [ f_name ]
+  - [ l_name ]
+formatting_sets:
+  - [ [ "{f_name}", "append:{0-2}" ], [ "_{l_name}", "k uppercase_all" ] ]
+  - [ [ "{f_name}", "if_length:>10" ], "{l_name}" ]
+  - [ "{l_name}.{f_name}" ]
+"#;
 
-        assert_eq!(mutation_result.mutated_words.len(), 26);
+    #[test]
+    fn cartesian_product_of_parts() {
+        let fmt_sets = parse_formatting_yaml(FORMATS);
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "john"),
+            FormatFieldAnswer::new("l_name", "smith"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(
+            words,
+            vec![
+                "john0_smith",
+                "john0_SMITH",
+                "john1_smith",
+                "john1_SMITH",
+                "john2_smith",
+                "john2_SMITH",
+                "smith.john"
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_value_fields() {
+        let fmt_sets = parse_formatting_yaml(
+            r#"
+name: test
+fields:
+  - [ f_name ]
+  - [ pet ]
+formatting_sets:
+  - [ "{f_name}" ]
+  - [ "{pet}", [ "{f_name}", 1st_letter ] ]
+"#,
+        );
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "john, mike"),
+            FormatFieldAnswer::new("pet", "rex,,fluffy"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(
+            words,
+            vec!["john", "mike", "rexj", "fluffyj", "rexm", "fluffym"]
+        );
+    }
+
+    #[test]
+    fn date_fields() {
+        assert_eq!(Date::parse("15/04/1990"), Date::parse("15041990"));
+        assert_eq!(Date::parse("1990-04-15"), Date::parse("15.04.1990"));
+        assert_eq!(Date::parse("32/01/1990"), None);
+        assert_eq!(Date::parse("1504"), None);
+        assert_eq!(Date::parse("xéééx"), None);
+        assert_eq!(Date::parse("éé/04/1990"), None);
+        assert_eq!(Date::parse("31/04/1990"), None);
+        assert_eq!(Date::parse("29/02/2023"), None);
+        assert_eq!(Date::parse("29/02/1900"), None);
+        assert!(Date::parse("29/02/2024").is_some());
+        assert!(Date::parse("29/02/2000").is_some());
+
+        let fmt_sets = parse_formatting_yaml(
+            r#"
+name: test
+fields:
+  - { name: dob, question: "Birthdate", type: date }
+formatting_sets:
+  - [ "{dob.dd}-{dob.mm}-{dob.yy}" ]
+  - [ "{dob.mon}{dob.yyyy}" ]
+"#,
+        );
+        let answers = vec![FormatFieldAnswer::new("dob", "1990-04-05, 5, 01/12/2001")];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words, vec!["05-04-90", "01-12-01", "apr1990", "dec2001"]);
+    }
+
+    #[test]
+    fn optional_fields() {
+        let fmt_sets = parse_formatting_yaml(
+            r#"
+name: test
+fields:
+  - [ f_name ]
+  - { name: m_name, optional: true }
+  - [ l_name ]
+  - [ nickname ]
+formatting_sets:
+  - [ "{f_name}", ".{m_name}", ".{l_name}" ]
+  - [ "{m_name}" ]
+  - [ "{nickname}{l_name}" ]
+  - [ "{f_name}.{unknown}" ]
+"#,
+        );
+        assert_eq!(
+            fmt_sets.check_placeholders(),
+            vec!["unknown placeholder {unknown}"]
+        );
+
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "john"),
+            FormatFieldAnswer::new("m_name", ""),
+            FormatFieldAnswer::new("l_name", "smith"),
+            FormatFieldAnswer::new("nickname", ""),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words, vec!["john.smith", "john.{unknown}"]);
+    }
+
+    #[test]
+    fn values_are_not_expanded() {
+        let fmt_sets = parse_formatting_yaml(
+            r#"
+name: test
+fields:
+  - [ f_name ]
+  - [ l_name ]
+formatting_sets:
+  - [ "{f_name}.{l_name}" ]
+"#,
+        );
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "{l_name}"),
+            FormatFieldAnswer::new("l_name", "smith"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words, vec!["{l_name}.smith"]);
+    }
+
+    #[test]
+    fn placeholder_functions() {
+        let fmt_sets = parse_formatting_yaml(
+            r#"
+name: test
+fields:
+  - [ f_name ]
+  - [ l_name ]
+  - [ company ]
+  - { name: dob, type: date }
+formatting_sets:
+  - [ "{f_name|first:2}{l_name|upper}" ]
+  - [ "{company|initials|lower}{dob|year}" ]
+  - [ "{l_name|last:3|reverse|capitalize}{dob.dd}" ]
+  - [ "{f_name|year}{f_name|first}{l_name|shout}" ]
+"#,
+        );
+        assert_eq!(
+            fmt_sets.check_placeholders(),
+            vec![
+                "invalid placeholder {f_name|year}: year can only be used for date fields",
+                "invalid placeholder {f_name|first}: first needs a number, like first:2",
+                "invalid placeholder {l_name|shout}: unknown function shout",
+            ]
+        );
+
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "John"),
+            FormatFieldAnswer::new("l_name", "Smith"),
+            FormatFieldAnswer::new("company", "Acme Widget-Works"),
+            FormatFieldAnswer::new("dob", "05/04/1990"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words[..3], ["JoSMITH", "aww1990", "Hti05"]);
+    }
+
+    #[test]
+    fn corporate_formats() {
+        for (_, yaml) in built_in_sets::FORMATTING_SETS {
+            assert!(parse_formatting_yaml(yaml).check_placeholders().is_empty());
+        }
+
+        let yaml = built_in_sets::find_formatting_sets("corporate_emails").unwrap();
+        let fmt_sets = parse_formatting_yaml(yaml);
+        // no middle name column, the formats using it are left out
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "Zoë"),
+            FormatFieldAnswer::new("l_name", "Smith"),
+            FormatFieldAnswer::new("domain", "Example.com"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words[0], "zoe.smith@example.com");
+        assert_eq!(words[1], "zsmith@example.com");
+        assert!(words.iter().all(|w| !w.contains('{') && !w.contains("..")));
+        assert_eq!(words.len(), 17);
+
+        let mut unique = words.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), words.len());
+
+        // smith and smithj are ranked before johns, further down in the file
+        let mut fmt_sets = parse_formatting_yaml(yaml);
+        fmt_sets.keep_top_formats(8);
+        let answers = vec![
+            FormatFieldAnswer::new("f_name", "John"),
+            FormatFieldAnswer::new("l_name", "Smith"),
+            FormatFieldAnswer::new("domain", "example.com"),
+        ];
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        assert_eq!(words.len(), 8);
+        assert!(words.contains(&String::from("smithj@example.com")));
+        assert!(!words.contains(&String::from("johns@example.com")));
+    }
+
+    #[test]
+    fn built_in_profile() {
+        let fmt_sets = parse_formatting_yaml(built_in_sets::PROFILE);
+        let answers = vec![
+            FormatFieldAnswer::new("name", "John"),
+            FormatFieldAnswer::new("surname", "Smith"),
+            FormatFieldAnswer::new("birthdate", "15/04/1990"),
+            FormatFieldAnswer::new("pet", "rex"),
+        ];
+
+        let words: Vec<String> = fmt_sets.generate_words(answers).collect();
+        for word in [
+            "1990",
+            "150490",
+            "jsmith",
+            "JohnSmith",
+            "j0hn",
+            "Rex1990",
+            "rex_1990",
+            "Smith7",
+            "JOHN",
+            "nhoj",
+            "john.smith",
+            "John12!",
+            "rex123",
+        ] {
+            assert!(words.iter().any(|w| w == word), "{word} not generated");
+        }
+
+        let mut unique = words.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), words.len());
     }
 }
 
@@ -165,3 +462,871 @@ mod scrape_tests {
         assert!(!content.contains("second"));
     }
 }
+
+#[cfg(test)]
+mod keyboard_walk_tests {
+    use crate::keyboard_walk::{Direction, KeyWalkOptions, Keyboard, Layout};
+
+    #[test]
+    fn qwerty_adjacency() {
+        let keyboard = Keyboard::new(Layout::Qwerty);
+        let q = keyboard.keys.iter().position(|k| k.normal == 'q').unwrap();
+        let a = keyboard.neighbour(q, Direction::DownRight).unwrap();
+        let one = keyboard.neighbour(q, Direction::UpLeft).unwrap();
+
+        assert_eq!(keyboard.keys[a].normal, 'a');
+        assert_eq!(keyboard.keys[one].normal, '1');
+    }
+
+    #[test]
+    fn walks_with_turns_and_shift() {
+        let keyboard = Keyboard::new(Layout::Qwerty);
+        let walks: Vec<String> = keyboard
+            .walks(KeyWalkOptions {
+                min_length: 8,
+                max_length: 8,
+                max_turns: 2,
+                shift: true,
+                max_shift_changes: 1,
+                max_segments: 1,
+            })
+            .collect();
+
+        assert!(walks.contains(&String::from("zaq1@WSX")));
+        assert!(walks.contains(&String::from("qwertyui")));
+        assert!(walks.iter().all(|w| w.chars().count() == 8));
+    }
+
+    #[test]
+    fn walks_without_shift() {
+        let keyboard = Keyboard::new(Layout::Qwerty);
+        let walks: Vec<String> = keyboard
+            .walks(KeyWalkOptions {
+                min_length: 3,
+                max_length: 4,
+                max_turns: 0,
+                shift: false,
+                max_shift_changes: 1,
+                max_segments: 1,
+            })
+            .collect();
+
+        assert!(walks.contains(&String::from("asdf")));
+        assert!(walks.contains(&String::from("1qa")));
+        assert!(!walks.contains(&String::from("asdfg")));
+        assert!(!walks.iter().any(|w| w.chars().any(|c| c.is_uppercase())));
+    }
+
+    #[test]
+    fn parallel_segments() {
+        let keyboard = Keyboard::new(Layout::Qwerty);
+        let walks: Vec<String> = keyboard
+            .walks(KeyWalkOptions {
+                min_length: 8,
+                max_length: 8,
+                max_turns: 0,
+                shift: true,
+                max_shift_changes: 1,
+                max_segments: 2,
+            })
+            .collect();
+
+        for walk in ["1qaz2wsx", "1qaz@WSX", "qwerasdf", "zaq1xsw2", "qwertyui"] {
+            assert!(walks.iter().any(|w| w == walk), "{walk} not generated");
+        }
+        // the second segment repeats the moves of the first one
+        assert!(!walks.contains(&String::from("1qaz2wer")));
+        // jumping along the segment would repeat its keys
+        assert!(!walks.contains(&String::from("qwerwert")));
+    }
+}
+
+#[cfg(test)]
+mod combinator_tests {
+    use crate::{
+        combinator::Combinator,
+        mutation::{parse_mutation_string, MutationSet},
+    };
+
+    fn mutations(mutation: &str) -> MutationSet {
+        MutationSet {
+            mutations: parse_mutation_string(&vec![mutation.to_owned()]),
+        }
+    }
+
+    #[test]
+    fn combine_words() {
+        let right_words = ["1", "2", "1"].map(String::from).into_iter();
+        let combinator = Combinator::new(
+            right_words,
+            mutations("k uppercase_all"),
+            &MutationSet::empty_set(),
+            vec![String::from(""), String::from("_"), String::from("_")],
+        );
+
+        let mut words = Vec::new();
+        for left_word in ["joe", "123"] {
+            combinator.combine(left_word, |w| words.push(w));
+        }
+
+        // "123" is the same uppercased, it's combined only once
+        assert_eq!(
+            words,
+            vec![
+                "joe1", "joe2", "joe_1", "joe_2", "JOE1", "JOE2", "JOE_1", "JOE_2", "1231", "1232",
+                "123_1", "123_2",
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod prince_tests {
+    use crate::prince::{prince_iterator, PrinceOptions};
+
+    #[test]
+    fn chains_by_keyspace() {
+        let words = vec![String::from("a"), String::from("b"), String::from("cd")];
+        let chains: Vec<String> = prince_iterator(
+            words,
+            PrinceOptions {
+                max_elements: 2,
+                min_length: 1,
+                max_length: 16,
+            },
+        )
+        .collect();
+
+        assert_eq!(
+            chains,
+            vec!["cd", "cdcd", "a", "b", "acd", "bcd", "cda", "cdb", "aa", "ab", "ba", "bb"]
+        )
+    }
+
+    #[test]
+    fn chains_only_fitting_lengths() {
+        // every word is used once or twice in 4 letters at most
+        let words: Vec<String> = (0..300).map(|i| format!("w{i}")).collect();
+        let chains = prince_iterator(
+            words,
+            PrinceOptions {
+                max_elements: 3,
+                min_length: 1,
+                max_length: 4,
+            },
+        );
+
+        // w0-w9 are 2 letters long, the only ones that fit twice
+        assert_eq!(chains.calculate_total(), 290 + 10 + 100);
+        assert_eq!(chains.count(), 400);
+    }
+
+    #[test]
+    fn chains_length_range() {
+        let words = vec![
+            String::from("pass"),
+            String::from("word"),
+            String::from("1"),
+            String::from("pass"),
+        ];
+        let chains: Vec<String> = prince_iterator(
+            words,
+            PrinceOptions {
+                max_elements: 3,
+                min_length: 8,
+                max_length: 9,
+            },
+        )
+        .collect();
+
+        assert!(chains.contains(&String::from("password")));
+        assert!(chains.contains(&String::from("password1")));
+        assert!(chains.contains(&String::from("1passpass")));
+        assert!(!chains.contains(&String::from("pass1")));
+        assert_eq!(chains.iter().filter(|c| *c == "passpass").count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod input_tests {
+    use std::fs;
+
+    use crate::input::{expand_input_paths, STDIN_PATH};
+
+    #[test]
+    fn expand_directories() {
+        let dir = std::env::temp_dir().join("gorilla_expand_directories");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.txt"), "hello\n").unwrap();
+        fs::write(dir.join("nested").join("b.txt"), "world\n").unwrap();
+        fs::write(dir.join("binary"), [0u8, 159, 146, 150]).unwrap();
+        // links back to the directory and its parent aren't followed
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(".", dir.join("self")).unwrap();
+            std::os::unix::fs::symlink("..", dir.join("nested").join("up")).unwrap();
+        }
+
+        let paths =
+            expand_input_paths(&[dir.to_string_lossy().into_owned(), String::from(STDIN_PATH)]);
+
+        assert_eq!(paths.len(), 3);
+        assert!(paths[0].ends_with("a.txt"));
+        assert!(paths[1].ends_with("b.txt"));
+        assert_eq!(paths[2], STDIN_PATH);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use std::io::{BufRead, Cursor, Write};
+
+    use crate::compression::{compress_writer, decompress_reader, Compression};
+
+    #[test]
+    fn compression_round_trip() {
+        for compression in [
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+            Compression::Xz,
+        ] {
+            let path = std::env::temp_dir().join(format!("gorilla_round_trip.{compression}"));
+            let file = std::fs::File::create(&path).unwrap();
+
+            let mut writer = compress_writer(Box::new(file), compression).unwrap();
+            writer.write_all(b"hello\nworld\n").unwrap();
+            writer.finish().unwrap();
+
+            let compressed = std::fs::read(&path).unwrap();
+            assert_eq!(Compression::from_magic(&compressed), compression);
+
+            // detected from the magic bytes, the path has no known extension
+            let (reader, detected) =
+                decompress_reader(Box::new(Cursor::new(compressed)), "words").unwrap();
+            let words: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+
+            assert_eq!(detected, compression);
+            assert_eq!(words, vec!["hello", "world"]);
+
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn plain_text_magic() {
+        assert_eq!(
+            Compression::from_magic(b"BZhang\nBZh9\n"),
+            Compression::None
+        );
+        assert_eq!(Compression::from_magic(b"BZh91AY&SY"), Compression::Bzip2);
+    }
+
+    struct FullDisk;
+
+    impl Write for FullDisk {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("no space left on device"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn finish_errors() {
+        // the encoders buffer the words, the error can show up only with the trailer
+        for compression in [
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+            Compression::Xz,
+        ] {
+            let mut writer = compress_writer(Box::new(FullDisk), compression).unwrap();
+            let written = writer.write_all(b"hello\n");
+            assert!(written.is_err() || writer.finish().is_err());
+        }
+    }
+
+    #[test]
+    fn compression_from_extension() {
+        assert_eq!(Compression::from_extension("out.txt.gz"), Compression::Gzip);
+        assert_eq!(Compression::from_extension("out.ZST"), Compression::Zstd);
+        assert_eq!(Compression::from_extension("out.txt"), Compression::None);
+    }
+}
+
+#[cfg(test)]
+mod encoding_tests {
+    use crate::{
+        arguments::InvalidLines,
+        encoding::{decode_word, encode_word},
+        input::{read_words, DEFAULT_DELIMITER},
+    };
+
+    #[test]
+    fn decode_hex_words() {
+        assert_eq!(decode_word(b"$HEX[68656c6c6f]"), Ok(String::from("hello")));
+        assert_eq!(decode_word(b"$HEX[6g]"), Ok(String::from("$HEX[6g]")));
+        assert_eq!(decode_word(b"plain"), Ok(String::from("plain")));
+    }
+
+    #[test]
+    fn invalid_utf8_round_trip() {
+        let escaped = decode_word(b"caf\xe9").unwrap_err();
+
+        assert_eq!(escaped.chars().count(), 4);
+        assert_eq!(encode_word(&escaped, false), "$HEX[636166e9]");
+        assert_eq!(encode_word("café", false), "café");
+        assert_eq!(encode_word("café", true), "$HEX[636166c3a9]");
+        assert_eq!(encode_word("$HEX[00]", false), "$HEX[244845585b30305d]");
+    }
+
+    #[test]
+    fn private_use_round_trip() {
+        // the characters used for escaped bytes, but valid utf-8 in the input
+        let word = decode_word("a\u{f780}".as_bytes()).unwrap();
+        assert_eq!(encode_word(&word, false), "$HEX[61ef9e80]");
+
+        // $HEX[] lines are decoded even when the bytes aren't valid utf-8
+        let word = decode_word(b"$HEX[efa0bfe9]").unwrap();
+        assert_eq!(encode_word(&word, false), "$HEX[efa0bfe9]");
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let path = std::env::temp_dir().join("gorilla_invalid_lines.txt");
+        std::fs::write(&path, b"caf\xe9\nword\n$HEX[ff41]\n").unwrap();
+        let path = path.to_string_lossy();
+
+        let read = |mode| {
+            let mut words = read_words(&path, DEFAULT_DELIMITER, mode).unwrap();
+            let result: Vec<String> = words.by_ref().map(|w| w.unwrap()).collect();
+            (result, words.skipped)
+        };
+
+        let (words, skipped) = read(InvalidLines::Skip);
+        assert_eq!(skipped, 1);
+        assert_eq!(words[0], "word");
+        assert_eq!(encode_word(&words[1], false), "$HEX[ff41]");
+        assert_eq!(read(InvalidLines::Escape).0.len(), 3);
+
+        std::fs::remove_file(path.as_ref()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod dedup_tests {
+    use crate::dedup::Deduplicator;
+
+    #[test]
+    fn exact_dedup() {
+        let mut deduplicator = Deduplicator::exact();
+
+        assert!(deduplicator.insert("word"));
+        assert!(deduplicator.insert("drow"));
+        assert!(!deduplicator.insert("word"));
+    }
+
+    #[test]
+    fn bloom_dedup() {
+        let mut deduplicator = Deduplicator::bloom(1);
+        let inserted = (0..10000)
+            .filter(|i| deduplicator.insert(&format!("word{i}")))
+            .count();
+
+        assert_eq!(inserted, 10000);
+        assert!(!deduplicator.insert("word42"));
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use std::sync::Arc;
+
+    use crate::{
+        mutation::{Action, Mutation, MutationSet},
+        pipeline::WorkerPool,
+    };
+
+    #[test]
+    fn ordered_pool_output() {
+        let mutation_sets = Arc::new(vec![
+            MutationSet::empty_set(),
+            MutationSet {
+                mutations: vec![Mutation {
+                    action: Action::Reverse,
+                    times: 1,
+                    keep_original: false,
+                }],
+            },
+        ]);
+        let mut pool = WorkerPool::new(3, mutation_sets, true);
+
+        let mut results = Vec::new();
+        for i in 0..50 {
+            results.append(&mut pool.submit(vec![format!("ab{i}"), format!("cd{i}")]));
+        }
+        results.append(&mut pool.finish());
+
+        let words: Vec<String> = results
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, result)| result.mutated_words)
+            .collect();
+        let expected: Vec<String> = (0..50)
+            .flat_map(|i| {
+                let (ab, cd) = (format!("ab{i}"), format!("cd{i}"));
+                let (ba, dc) = (ab.chars().rev().collect(), cd.chars().rev().collect());
+                vec![ab, ba, cd, dc]
+            })
+            .collect();
+
+        assert_eq!(words, expected);
+    }
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use regex::Regex;
+
+    use crate::filters::{parse_char_classes, OutputFilter};
+
+    #[test]
+    fn length_and_classes() {
+        let filter = OutputFilter {
+            min_length: Some(8),
+            max_length: Some(10),
+            required_classes: parse_char_classes("ud").unwrap(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches("Password1"));
+        assert!(!filter.matches("password1"));
+        assert!(!filter.matches("Pass1"));
+        assert!(!filter.matches("Password1234"));
+    }
+
+    #[test]
+    fn min_classes_and_regexes() {
+        let filter = OutputFilter {
+            min_classes: 3,
+            allow: vec![Regex::new("^[A-Z]").unwrap()],
+            deny: vec![Regex::new("(?i)admin").unwrap()],
+            ..Default::default()
+        };
+
+        assert!(filter.matches("Summer2024!"));
+        assert!(!filter.matches("summer2024!"));
+        assert!(!filter.matches("Summer"));
+        assert!(!filter.matches("Admin2024!"));
+        assert!(parse_char_classes("lx").is_err());
+    }
+}
+
+#[cfg(test)]
+mod split_tests {
+    use std::fs;
+
+    use clap::Parser;
+
+    use crate::{
+        arguments::{parse_size, ProgramArgs},
+        compression::Compression,
+        output::{chunk_path, OutputWriter, SplitOptions, WriteMode},
+    };
+
+    #[test]
+    fn chunk_paths() {
+        assert_eq!(chunk_path("words.txt", 1, None), "words.0001.txt");
+        assert_eq!(chunk_path("out/words", 12, None), "out/words.0012");
+        assert_eq!(chunk_path("words.txt.gz", 2, None), "words.0002.txt.gz");
+        assert_eq!(
+            chunk_path("./words.txt", 3, Some(Compression::Zstd)),
+            "./words.0003.txt.zst"
+        );
+        assert_eq!(
+            chunk_path("out.d/.words.GZ", 1, None),
+            "out.d/.words.0001.gz"
+        );
+    }
+
+    #[test]
+    fn split_arguments() {
+        assert_eq!(parse_size("20mb"), Ok(20 * 1048576));
+        assert!(parse_size("99999999999999gb").is_err());
+
+        // nothing would be split
+        let args = ["gorilla", "-o", "words.txt", "--split-compress", "gzip"];
+        assert!(ProgramArgs::try_parse_from(args).is_err());
+        let args = [
+            "gorilla",
+            "-o",
+            "words.txt",
+            "--split-bytes",
+            "1mb",
+            "--split-compress",
+            "xz",
+        ];
+        assert!(ProgramArgs::try_parse_from(args).is_ok());
+    }
+
+    #[test]
+    fn split_by_words_and_bytes() {
+        let dir = std::env::temp_dir().join("gorilla_split_by_words_and_bytes");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("words.txt").to_string_lossy().into_owned();
+
+        let mut output = OutputWriter::split_files(
+            &path,
+            "\n",
+            false,
+            WriteMode::Overwrite,
+            SplitOptions {
+                max_words: Some(3),
+                max_bytes: Some(10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for word in ["a", "b", "c", "d", "longer", "e"] {
+            output.write_word(word).unwrap();
+        }
+        output.finish().unwrap();
+
+        let chunk = |n| fs::read_to_string(chunk_path(&path, n, None)).unwrap();
+        assert_eq!(chunk(1), "a\nb\nc\n");
+        assert_eq!(chunk(2), "d\nlonger\n");
+        assert_eq!(chunk(3), "e\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod hashing_tests {
+    use crate::{
+        encoding::decode_word,
+        hashing::{parse_hash_algorithms, HashAlgorithm},
+    };
+
+    #[test]
+    fn known_hashes() {
+        assert_eq!(
+            HashAlgorithm::Md5.hash("password"),
+            "5f4dcc3b5aa765d61d8327deb882cf99"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha1.hash("password"),
+            "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha256.hash("password"),
+            "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8"
+        );
+        assert_eq!(
+            HashAlgorithm::Ntlm.hash("password"),
+            "8846f7eaee8fb117ad06bdd830b7586c"
+        );
+    }
+
+    #[test]
+    fn hash_raw_bytes() {
+        let escaped = decode_word(b"$HEX[70617373776f7264]").unwrap();
+        assert_eq!(
+            HashAlgorithm::Md5.hash(&escaped),
+            "5f4dcc3b5aa765d61d8327deb882cf99"
+        );
+        assert_eq!(
+            parse_hash_algorithms("md5, SHA-256").unwrap(),
+            vec![HashAlgorithm::Md5, HashAlgorithm::Sha256]
+        );
+    }
+}
+
+#[cfg(test)]
+mod jsonl_tests {
+    use std::fs;
+
+    use crate::{
+        arguments::OutputFormat,
+        output::{chunk_path, OutputWriter, SplitOptions, WriteMode},
+    };
+
+    #[test]
+    fn jsonl_provenance() {
+        let path = std::env::temp_dir().join("gorilla_jsonl_provenance.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut output =
+            OutputWriter::file(&path.to_string_lossy(), "\n", false, WriteMode::Overwrite).unwrap();
+        output.format = OutputFormat::Jsonl;
+        output.mutation_chains = vec![String::from("nothing"), String::from("reverse")];
+        output.write_candidate("drow", "word", 1).unwrap();
+        output.finish().unwrap();
+
+        let line = fs::read_to_string(&path).unwrap();
+        let record: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+
+        assert_eq!(record["candidate"], "drow");
+        assert_eq!(record["original"], "word");
+        assert_eq!(record["mutation_set"], 1);
+        assert_eq!(record["mutations"], "reverse");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_split() {
+        let dir = std::env::temp_dir().join("gorilla_jsonl_split");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("words.jsonl").to_string_lossy().into_owned();
+
+        // the separator is ignored, json lines are always one per line
+        let mut output = OutputWriter::split_files(
+            &path,
+            " ",
+            false,
+            WriteMode::Overwrite,
+            SplitOptions {
+                max_words: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        output.format = OutputFormat::Jsonl;
+        output.mutation_chains = vec![String::from("nothing")];
+        for word in ["a", "b", "c"] {
+            output.write_candidate(word, word, 0).unwrap();
+        }
+        output.finish().unwrap();
+
+        let chunk = |n| fs::read_to_string(chunk_path(&path, n, None)).unwrap();
+        assert_eq!(chunk(1).lines().count(), 2);
+        assert!(chunk(1).ends_with('\n'));
+        assert_eq!(chunk(2).lines().count(), 1);
+
+        let record: serde_json::Value = serde_json::from_str(chunk(2).trim()).unwrap();
+        assert_eq!(record["candidate"], "c");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod separator_tests {
+    use std::io::Cursor;
+
+    use crate::{arguments::parse_separator, input::word_lines};
+
+    #[test]
+    fn separator_escapes() {
+        assert_eq!(parse_separator("\\0").unwrap(), "\0");
+        assert_eq!(parse_separator("\\t|\\\\").unwrap(), "\t|\\");
+        assert!(parse_separator("\\q").is_err());
+        assert!(parse_separator("").is_err());
+    }
+
+    #[test]
+    fn custom_input_delimiters() {
+        let lines = |input: &'static [u8], delimiter: &str| -> Vec<Vec<u8>> {
+            word_lines(Box::new(Cursor::new(input)), delimiter)
+                .map(|l| l.unwrap())
+                .collect()
+        };
+
+        assert_eq!(
+            lines(b"one\r\ntwo\nthree", "\n"),
+            vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]
+        );
+        assert_eq!(
+            lines(b"with space\0two\0", "\0"),
+            vec![b"with space".to_vec(), b"two".to_vec()]
+        );
+        assert_eq!(
+            lines(b"a::b:c::", "::"),
+            vec![b"a".to_vec(), b"b:c".to_vec()]
+        );
+    }
+}
+
+#[cfg(test)]
+mod write_mode_tests {
+    use std::fs;
+
+    use crate::output::{OutputWriter, WriteMode};
+
+    fn write(path: &str, words: &[&str], mode: WriteMode) -> std::io::Result<()> {
+        let mut output = OutputWriter::file(path, "\n", false, mode)?;
+        for word in words {
+            output.write_word(word)?;
+        }
+        output.finish()
+    }
+
+    #[test]
+    fn write_modes() {
+        let dir = std::env::temp_dir().join("gorilla_write_modes");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("words.txt").to_string_lossy().into_owned();
+
+        write(&path, &["a", "b"], WriteMode::Overwrite).unwrap();
+        write(&path, &["c"], WriteMode::Append).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+
+        write(&path, &["d"], WriteMode::Overwrite).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "d\n");
+
+        assert!(write(&path, &["e"], WriteMode::NoClobber).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "d\n");
+
+        // the file is only replaced once the output is finished
+        let mut output = OutputWriter::file(&path, "\n", false, WriteMode::Overwrite).unwrap();
+        output.write_word("f").unwrap();
+        output.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "d\n");
+        drop(output);
+
+        // created by someone else while the words were written
+        let new_path = dir.join("new.txt").to_string_lossy().into_owned();
+        let mut output = OutputWriter::file(&new_path, "\n", false, WriteMode::NoClobber).unwrap();
+        output.write_word("g").unwrap();
+        fs::write(&new_path, "theirs\n").unwrap();
+        assert!(output.finish().is_err());
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "theirs\n");
+        drop(output);
+        fs::remove_file(&new_path).unwrap();
+
+        // no temporary files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // written through, renaming would replace the link with a file
+        #[cfg(unix)]
+        {
+            let link = dir.join("link.txt");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            write(&link.to_string_lossy(), &["h"], WriteMode::Overwrite).unwrap();
+            assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+            assert_eq!(fs::read_to_string(&path).unwrap(), "h\n");
+            fs::remove_file(&link).unwrap();
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod profile_tests {
+    use std::fs;
+
+    use crate::profile_parser::{fmt_answers_from_profile, push_field_answers};
+
+    #[test]
+    fn profiles_and_fields() {
+        let path = std::env::temp_dir().join("gorilla_profiles_and_fields.json");
+        fs::write(
+            &path,
+            r#"[{"f_name": "john", "pets": ["rex", "fluffy"]}, {"f_name": "jane", "dob": 15041990, "pets": null}]"#,
+        )
+        .unwrap();
+
+        let mut profiles = fmt_answers_from_profile(&path.to_string_lossy()).unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0][1].values, vec!["rex", "fluffy"]);
+        assert_eq!(profiles[1][1].values, vec!["15041990"]);
+        assert!(profiles[1][2].values.is_empty());
+
+        let fields = vec![
+            (String::from("pets"), String::from("max")),
+            (String::from("domain"), String::from("example.com")),
+        ];
+        push_field_answers(&mut profiles[0], &fields);
+        assert_eq!(profiles[0][1].values, vec!["rex", "fluffy", "max"]);
+        assert_eq!(profiles[0][2].name, "domain");
+
+        // numbers keep their leading zeros
+        fs::write(&path, "dob: 01021990\nzip: 01234\nheight: 1.50\nnick: ~\n").unwrap();
+        let profiles = fmt_answers_from_profile(&path.to_string_lossy()).unwrap();
+        assert_eq!(profiles[0][0].values, vec!["01021990"]);
+        assert_eq!(profiles[0][1].values, vec!["01234"]);
+        assert_eq!(profiles[0][2].values, vec!["1.50"]);
+        assert!(profiles[0][3].values.is_empty());
+
+        fs::write(
+            &path,
+            r#"{"zip": 01234, "pets": &pets [rex], "more": *pets}"#,
+        )
+        .unwrap();
+        let profiles = fmt_answers_from_profile(&path.to_string_lossy()).unwrap();
+        assert_eq!(profiles[0][0].values, vec!["01234"]);
+        assert_eq!(profiles[0][2].values, vec!["rex"]);
+
+        fs::write(&path, "f_name: [ john ").unwrap();
+        assert!(fmt_answers_from_profile(&path.to_string_lossy()).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod csv_tests {
+    use std::fs;
+
+    use crate::{
+        csv_parser::{fmt_answers_from_csv, CsvOptions},
+        yaml_parser::parse_formatting_yaml,
+    };
+
+    #[test]
+    fn csv_mapping_and_errors() {
+        let fmt_sets = parse_formatting_yaml(
+            "name: test\nfields:\n  - [ f_name ]\n  - [ l_name ]\nformatting_sets:\n  - [ \"{f_name}.{l_name}\" ]\n",
+        );
+        let path = std::env::temp_dir().join("gorilla_csv_mapping_and_errors.csv");
+        let path_str = path.to_string_lossy();
+
+        fs::write(
+            &path,
+            "first;last;email\njohn;smith;j@x\njane;doe\nbob;ross;b@x\n",
+        )
+        .unwrap();
+
+        let mut options = CsvOptions {
+            delimiter: b';',
+            column_map: vec![
+                (String::from("first"), String::from("f_name")),
+                (String::from("last"), String::from("l_name")),
+            ],
+            ..Default::default()
+        };
+        assert!(fmt_answers_from_csv(&path_str, &fmt_sets.fields, &options).is_err());
+
+        // the short row is reported and skipped
+        options.ignore_extra_columns = true;
+        let rows = fmt_answers_from_csv(&path_str, &fmt_sets.fields, &options).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][0].values, vec!["bob"]);
+        assert_eq!(rows[1].len(), 2);
+
+        fs::write(&path, "john,smith\njane,doe\n").unwrap();
+        let options = CsvOptions {
+            has_headers: false,
+            ..Default::default()
+        };
+        let rows = fmt_answers_from_csv(&path_str, &fmt_sets.fields, &options).unwrap();
+        let words: Vec<String> = rows
+            .into_iter()
+            .flat_map(|row| fmt_sets.generate_words(row).collect::<Vec<String>>())
+            .collect();
+        assert_eq!(words, vec!["john.smith", "jane.doe"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
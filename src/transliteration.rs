@@ -0,0 +1,54 @@
+use deunicode::deunicode;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+// letters written with a stroke don't decompose into a base letter and a mark
+fn strip_stroke(ch: char) -> char {
+    match ch {
+        'ł' => 'l',
+        'Ł' => 'L',
+        'đ' => 'd',
+        'Đ' => 'D',
+        'ø' => 'o',
+        'Ø' => 'O',
+        'ħ' => 'h',
+        'Ħ' => 'H',
+        'ı' => 'i',
+        _ => ch,
+    }
+}
+
+// Zoë Łukasiewicz -> Zoe Lukasiewicz, other scripts are kept
+pub fn strip_diacritics(word: &str) -> String {
+    word.nfd()
+        .filter(|&ch| !is_combining_mark(ch))
+        .map(strip_stroke)
+        .nfc()
+        .collect()
+}
+
+// every character is replaced with its closest ascii spelling: Москва -> Moskva
+pub fn transliterate(word: &str) -> String {
+    deunicode(word)
+}
+
+// the spelling used when umlauts can't be typed: Müller -> Mueller, Bjørn -> Bjoern
+pub fn expand_umlauts(word: &str) -> String {
+    let mut result = String::with_capacity(word.len());
+
+    for ch in word.chars() {
+        match ch {
+            'ä' | 'æ' => result.push_str("ae"),
+            'ö' | 'ø' => result.push_str("oe"),
+            'ü' => result.push_str("ue"),
+            'å' => result.push_str("aa"),
+            'ß' => result.push_str("ss"),
+            'Ä' | 'Æ' => result.push_str("Ae"),
+            'Ö' | 'Ø' => result.push_str("Oe"),
+            'Ü' => result.push_str("Ue"),
+            'Å' => result.push_str("Aa"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
@@ -1,10 +1,10 @@
 extern crate yaml_rust;
-use yaml_rust::YamlLoader;
+use yaml_rust::{Yaml, YamlLoader};
 
 use colored::Colorize;
 
 use crate::{
-    formatting::{FormatField, FormatPart, FormatSet, FormattingSets},
+    formatting::{FieldType, FormatField, FormatPart, FormatSet, FormattingSets},
     mutation::{parse_mutation_string, MutationSet},
 };
 
@@ -49,23 +49,60 @@ pub fn parse_formatting_yaml(yaml_input: &str) -> FormattingSets {
     let mut format_fields: Vec<FormatField> = Vec::new();
 
     for yaml_format_field in doc["fields"].as_vec().unwrap() {
-        let fields = yaml_format_field.as_vec().unwrap();
-        let mut question: Option<String> = None;
-
-        if fields.len() > 1 {
-            question = fields[1].as_str().map(str::to_owned)
-        }
+        // either [ name, question ] or { name: .., question: .., type: .., optional: .. }
+        let (name, question, field_type, optional) = match yaml_format_field.as_vec() {
+            Some(fields) => (&fields[0], fields.get(1), None, false),
+            None => (
+                &yaml_format_field["name"],
+                Some(&yaml_format_field["question"]),
+                yaml_format_field["type"].as_str(),
+                yaml_format_field["optional"].as_bool().unwrap_or(false),
+            ),
+        };
+
+        let field_type = match field_type {
+            None | Some("text") => FieldType::Text,
+            Some("date") => FieldType::Date,
+            Some(field_type) => {
+                eprintln!(
+                    "gorilla: (error) unknown field type: {field_type}. Valid types: text, date"
+                );
+                panic!()
+            }
+        };
 
         format_fields.push(FormatField {
-            name: fields[0].as_str().unwrap().to_owned(),
-            question,
+            name: name.as_str().unwrap().to_owned(),
+            question: question.and_then(|q| q.as_str()).map(str::to_owned),
+            field_type,
+            optional,
         })
     }
 
     for yaml_format_set in doc["formatting_sets"].as_vec().unwrap() {
         let mut format_set = FormatSet::new();
 
-        for fmt_part in yaml_format_set.as_vec().unwrap() {
+        // either [ parts.. ] or { rank: .., parts: [ parts.. ] }
+        let fmt_parts = match yaml_format_set.as_vec() {
+            Some(fmt_parts) => fmt_parts,
+            None => {
+                format_set.rank = match &yaml_format_set["rank"] {
+                    Yaml::BadValue => None,
+                    rank => match rank.as_i64().and_then(|r| u32::try_from(r).ok()) {
+                        Some(rank) => Some(rank),
+                        None => {
+                            eprintln!(
+                                "gorilla: (error) invalid format rank: {rank:?}, expected a positive number"
+                            );
+                            panic!()
+                        }
+                    },
+                };
+                yaml_format_set["parts"].as_vec().unwrap()
+            }
+        };
+
+        for fmt_part in fmt_parts {
             if let Some(fmt_str) = fmt_part.as_str() {
                 format_set.parts.push(FormatPart {
                     text: fmt_str.to_owned(),
@@ -101,9 +138,15 @@ pub fn parse_formatting_yaml(yaml_input: &str) -> FormattingSets {
         format_sets.push(format_set)
     }
 
-    FormattingSets {
+    let formatting_sets = FormattingSets {
         name: formatting_name.to_owned(),
         fields: format_fields,
         sets: format_sets,
+    };
+
+    for problem in formatting_sets.check_placeholders() {
+        eprintln!("gorilla: (warning) {problem}");
     }
+
+    formatting_sets
 }